@@ -3,6 +3,28 @@
 
 #[macro_use]
 extern crate crypto_tests;
+extern crate generic_array;
 extern crate groestl;
 
+use generic_array::GenericArray;
+use generic_array::typenum::U64;
+
 bench_digest!(groestl::Groestl256);
+bench_digest!(bench_1m, groestl::Groestl256, 1 << 20);
+
+#[bench]
+fn bench_compress(b: &mut Bencher) {
+    let mut h = GenericArray::<u8, U64>::default();
+    let m = GenericArray::<u8, U64>::default();
+
+    b.iter(|| {
+        groestl::compress(&mut h, &m);
+    });
+
+    b.bytes = m.len() as u64;
+}
+
+#[bench]
+fn bench_new(b: &mut Bencher) {
+    b.iter(|| core::hint::black_box(groestl::Groestl256::default()));
+}
@@ -0,0 +1,61 @@
+//! P and Q permutations using AES-NI and SSSE3.
+//!
+//! Groestl's S-box is the AES S-box, so `SubBytes` is `aesenclast` with a zero
+//! round key once the AES `ShiftRows` it applies has been undone by a
+//! `pshufb`. That shuffle is folded into the one implementing `ShiftBytes`.
+//! The state layout, round constants and `MixBytes` are shared with `ssse3`.
+
+use core::arch::x86_64::*;
+
+use ssse3::{add_round_constant, load, mix_bytes, store};
+
+/// Whether the running CPU supports the instructions `permute` needs.
+pub fn available() -> bool {
+    is_x86_feature_detected!("aes") && is_x86_feature_detected!("ssse3")
+}
+
+/// Permutes a 64 or 128 byte block in place. `q` selects the Q round
+/// constants and `original` the single-byte constants of Groestl-0.
+///
+/// # Safety
+///
+/// The caller must ensure that `available()` returned `true`.
+#[target_feature(enable = "aes,ssse3")]
+pub unsafe fn permute(
+    block: &mut [u8],
+    rounds: u8,
+    shifts: [u8; 8],
+    q: bool,
+    original: bool,
+) {
+    let cols = block.len() / 8;
+    let mut rows = load(block);
+
+    // Per-row shuffle applying ShiftBytes followed by the inverse of AES
+    // ShiftRows, which `aesenclast` then reapplies.
+    let mut masks = [_mm_setzero_si128(); 8];
+    for (mask, &shift) in masks.iter_mut().zip(shifts.iter()) {
+        let mut m = [0x80u8; 16];
+        for (i, b) in m.iter_mut().enumerate() {
+            let (r, c) = (i % 4, i / 4);
+            let j = r + 4 * ((c + 4 - r) % 4);
+            if j < cols {
+                *b = ((j + shift as usize) % cols) as u8;
+            }
+        }
+        *mask = _mm_loadu_si128(m.as_ptr() as *const __m128i);
+    }
+
+    let zero = _mm_setzero_si128();
+
+    for round in 0..rounds {
+        add_round_constant(&mut rows, round, q, original);
+        for (row, mask) in rows.iter_mut().zip(masks.iter()) {
+            *row = _mm_aesenclast_si128(_mm_shuffle_epi8(*row, *mask), zero);
+        }
+
+        rows = mix_bytes(&rows);
+    }
+
+    store(&rows, block);
+}
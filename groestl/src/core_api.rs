@@ -0,0 +1,289 @@
+//! The traits of `digest` 0.10, so that the hashers work with the current
+//! RustCrypto crates such as `hmac` and `hkdf`.
+//!
+//! `GroestlSmall` and `GroestlBig` implement the high-level traits directly,
+//! which is enough for `SimpleHmac` and makes them `DynDigest` through
+//! `digest`'s blanket impl (whose boxed methods need the `alloc` feature).
+//! Their `OutputSize` is the digest size, so `digest::Output<Groestl256>` is
+//! a 32-byte array (of `generic-array` 0.14, which `digest` 0.10 uses).
+//! `Hmac` needs a type built from a block-level core, such as the aliases
+//! here:
+//!
+//! ```rust,ignore
+//! let mac = Hmac::<groestl::core_api::Groestl256>::new_from_slice(key);
+//! ```
+//!
+//! In those the input is buffered by the `block-buffer` crate inside
+//! `digest`'s `CoreWrapper`, and `GroestlCore` only compresses whole blocks
+//! and pads.
+//!
+//! `Reset`, `FixedOutputReset` and `DynDigest::finalize_reset` behave like
+//! the inherent `reset`: they go back to the standard IV and discard a
+//! custom IV or prefix.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use byte_tools::write_u64_be;
+use digest010::{
+    FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset,
+    Update,
+};
+use digest010::block_buffer::Eager;
+use digest010::core_api::{
+    AlgorithmName, Block, BlockSizeUser, Buffer, BufferKindUser,
+    CoreWrapper, FixedOutputCore, UpdateCore,
+};
+use digest010::generic_array::ArrayLength as ModernArrayLength;
+use digest010::typenum::{IsLess, Le, NonZero, U256};
+use generic_array::{ArrayLength, GenericArray};
+use generic_array::typenum::{U28, U32, U48, U64, U128};
+
+use groestl::{compress, output_transform, ValidBlockSize, ValidOutputSize};
+use {BigOutputSize, GroestlBig, GroestlSmall, SmallOutputSize};
+
+/// The block-level Groestl core for `digest`'s `CoreWrapper`.
+///
+/// It computes the same digest as `Groestl<OutputSize, BlockSize>` from the
+/// public `compress` and `output_transform` primitives.
+#[derive(Clone)]
+pub struct GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    h: GenericArray<u8, BlockSize>,
+    num_blocks: u64,
+    output: PhantomData<OutputSize>,
+}
+
+/// Groestl-224 built on `GroestlCore`.
+pub type Groestl224 = CoreWrapper<GroestlCore<U28, U64>>;
+/// Groestl-256 built on `GroestlCore`.
+pub type Groestl256 = CoreWrapper<GroestlCore<U32, U64>>;
+/// Groestl-384 built on `GroestlCore`.
+pub type Groestl384 = CoreWrapper<GroestlCore<U48, U128>>;
+/// Groestl-512 built on `GroestlCore`.
+pub type Groestl512 = CoreWrapper<GroestlCore<U64, U128>>;
+
+impl<OutputSize, BlockSize> Default for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    fn default() -> Self {
+        let mut h = GenericArray::<u8, BlockSize>::default();
+        let len = h.len();
+        write_u64_be(&mut h[len - 8..], 8 * OutputSize::to_u64());
+        GroestlCore { h, num_blocks: 0, output: PhantomData }
+    }
+}
+
+impl<OutputSize, BlockSize> HashMarker for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize + ModernArrayLength<u8> + 'static,
+          <BlockSize as ArrayLength<u8>>::ArrayType: Copy,
+{}
+
+impl<OutputSize, BlockSize> BlockSizeUser
+    for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize + ModernArrayLength<u8> + 'static,
+          <BlockSize as ArrayLength<u8>>::ArrayType: Copy,
+{
+    type BlockSize = BlockSize;
+}
+
+impl<OutputSize, BlockSize> BufferKindUser
+    for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize + ModernArrayLength<u8> + 'static,
+          <BlockSize as ArrayLength<u8>>::ArrayType: Copy,
+{
+    type BufferKind = Eager;
+}
+
+impl<OutputSize, BlockSize> OutputSizeUser
+    for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>
+              + ModernArrayLength<u8> + 'static,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    type OutputSize = OutputSize;
+}
+
+impl<OutputSize, BlockSize> UpdateCore for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize + ModernArrayLength<u8> + 'static,
+          <BlockSize as ArrayLength<u8>>::ArrayType: Copy,
+{
+    fn update_blocks(&mut self, blocks: &[Block<Self>]) {
+        self.num_blocks += blocks.len() as u64;
+        for block in blocks {
+            compress(&mut self.h, GenericArray::from_slice(&block[..]));
+        }
+    }
+}
+
+impl<OutputSize, BlockSize> FixedOutputCore
+    for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>
+              + ModernArrayLength<u8> + 'static,
+          BlockSize: ValidBlockSize + ModernArrayLength<u8> + 'static,
+          BlockSize: IsLess<U256>,
+          Le<BlockSize, U256>: NonZero,
+          <BlockSize as ArrayLength<u8>>::ArrayType: Copy,
+{
+    fn finalize_fixed_core(
+        &mut self,
+        buffer: &mut Buffer<Self>,
+        out: &mut Output<Self>,
+    ) {
+        // The length field counts blocks, including the padding block, and
+        // the 0x80 byte and the field need a second one if fewer than 9
+        // bytes are free.
+        let num_blocks = if buffer.remaining() <= 8 {
+            self.num_blocks + 2
+        } else {
+            self.num_blocks + 1
+        };
+        let h = &mut self.h;
+        buffer.len64_padding_be(num_blocks, |block| {
+            compress(h, GenericArray::from_slice(&block[..]));
+        });
+        out.copy_from_slice(&output_transform::<OutputSize, BlockSize>(h));
+    }
+}
+
+impl<OutputSize, BlockSize> Reset for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<OutputSize, BlockSize> AlgorithmName
+    for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    fn write_alg_name(f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Groestl{}", 8 * OutputSize::to_usize())
+    }
+}
+
+// Deliberately leaves out the chaining value, like `Groestl`'s.
+impl<OutputSize, BlockSize> fmt::Debug for GroestlCore<OutputSize, BlockSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GroestlCore")
+            .field("output_size", &OutputSize::to_usize())
+            .field("block_size", &BlockSize::to_usize())
+            .field("blocks_processed", &self.num_blocks)
+            .finish()
+    }
+}
+
+impl<OutputSize> HashMarker for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{}
+
+impl<OutputSize> BlockSizeUser for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    type BlockSize = U64;
+}
+
+impl<OutputSize> OutputSizeUser for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize + ModernArrayLength<u8> + 'static,
+{
+    type OutputSize = OutputSize;
+}
+
+impl<OutputSize> Update for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    fn update(&mut self, data: &[u8]) {
+        self.groestl.process(data);
+    }
+}
+
+impl<OutputSize> FixedOutput for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize + ModernArrayLength<u8> + 'static,
+{
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.groestl.finalize());
+    }
+}
+
+impl<OutputSize> FixedOutputReset for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize + ModernArrayLength<u8> + 'static,
+{
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.groestl.finalize_reset());
+    }
+}
+
+impl<OutputSize> Reset for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    fn reset(&mut self) {
+        self.groestl.reset();
+    }
+}
+
+impl<OutputSize> HashMarker for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{}
+
+impl<OutputSize> BlockSizeUser for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    type BlockSize = U128;
+}
+
+impl<OutputSize> OutputSizeUser for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize + ModernArrayLength<u8> + 'static,
+{
+    type OutputSize = OutputSize;
+}
+
+impl<OutputSize> Update for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    fn update(&mut self, data: &[u8]) {
+        self.groestl.process(data);
+    }
+}
+
+impl<OutputSize> FixedOutput for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize + ModernArrayLength<u8> + 'static,
+{
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.groestl.finalize());
+    }
+}
+
+impl<OutputSize> FixedOutputReset for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize + ModernArrayLength<u8> + 'static,
+{
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.groestl.finalize_reset());
+    }
+}
+
+impl<OutputSize> Reset for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    fn reset(&mut self) {
+        self.groestl.reset();
+    }
+}
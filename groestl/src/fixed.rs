@@ -0,0 +1,125 @@
+//! Groestl with the digest size as a const generic parameter.
+
+use core::fmt;
+
+use digest::Digest;
+use generic_array::GenericArray;
+use generic_array::typenum::{
+    U1, U2, U3, U4, U5, U6, U7, U8, U9, U10, U11, U12, U13, U14, U15, U16,
+    U17, U18, U19, U20, U21, U22, U23, U24, U25, U26, U27, U28, U29, U30,
+    U31, U32, U33, U34, U35, U36, U37, U38, U39, U40, U41, U42, U43, U44,
+    U45, U46, U47, U48, U49, U50, U51, U52, U53, U54, U55, U56, U57, U58,
+    U59, U60, U61, U62, U63, U64,
+};
+
+use {GroestlBig, GroestlSmall};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A digest size of `N` bytes, as a type that `Groestl` can look up.
+pub struct OutputLen<const N: usize>;
+
+/// Digest sizes `Groestl` is defined for, 1 to 64 bytes. This trait is
+/// sealed.
+pub trait ValidOutputLen: sealed::Sealed {
+    /// The `GroestlSmall` or `GroestlBig` with the same digest size.
+    type Hasher: Digest + Clone + Default + fmt::Debug;
+}
+
+macro_rules! output_lens {
+    ($hasher:ident: $($n:expr => $size:ident),*) => { $(
+        impl sealed::Sealed for OutputLen<$n> {}
+
+        impl ValidOutputLen for OutputLen<$n> {
+            type Hasher = $hasher<$size>;
+        }
+    )* };
+}
+
+output_lens!(GroestlSmall:
+    1 => U1, 2 => U2, 3 => U3, 4 => U4, 5 => U5, 6 => U6, 7 => U7,
+    8 => U8, 9 => U9, 10 => U10, 11 => U11, 12 => U12, 13 => U13,
+    14 => U14, 15 => U15, 16 => U16, 17 => U17, 18 => U18, 19 => U19,
+    20 => U20, 21 => U21, 22 => U22, 23 => U23, 24 => U24, 25 => U25,
+    26 => U26, 27 => U27, 28 => U28, 29 => U29, 30 => U30, 31 => U31,
+    32 => U32
+);
+
+output_lens!(GroestlBig:
+    33 => U33, 34 => U34, 35 => U35, 36 => U36, 37 => U37, 38 => U38,
+    39 => U39, 40 => U40, 41 => U41, 42 => U42, 43 => U43, 44 => U44,
+    45 => U45, 46 => U46, 47 => U47, 48 => U48, 49 => U49, 50 => U50,
+    51 => U51, 52 => U52, 53 => U53, 54 => U54, 55 => U55, 56 => U56,
+    57 => U57, 58 => U58, 59 => U59, 60 => U60, 61 => U61, 62 => U62,
+    63 => U63, 64 => U64
+);
+
+/// Groestl producing `OUT` bytes, with the size as a const generic rather
+/// than a `typenum` parameter.
+///
+/// This is a thin wrapper over `GroestlSmall` for up to 32 bytes and
+/// `GroestlBig` above, so `Groestl<32>` computes the same digest as
+/// `Groestl256`. `finalize` returns a plain `[u8; OUT]`.
+///
+/// ```rust
+/// use groestl::{Digest, Groestl, Groestl512};
+///
+/// let mut hasher = Groestl::<64>::new();
+/// hasher.input(b"my message");
+/// let digest: [u8; 64] = hasher.finalize();
+/// assert_eq!(digest[..], Groestl512::digest(b"my message")[..]);
+/// ```
+///
+/// Sizes outside 1 to 64 bytes do not compile:
+///
+/// ```rust,compile_fail
+/// let hasher = groestl::Groestl::<65>::new();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Groestl<const OUT: usize>
+    where OutputLen<OUT>: ValidOutputLen,
+{
+    inner: <OutputLen<OUT> as ValidOutputLen>::Hasher,
+}
+
+impl<const OUT: usize> Groestl<OUT>
+    where OutputLen<OUT>: ValidOutputLen,
+{
+    /// Creates a hasher. The same as `default()`.
+    pub fn new() -> Self {
+        Groestl::default()
+    }
+
+    /// Computes the digest of `input` in one step.
+    pub fn digest(input: &[u8]) -> [u8; OUT] {
+        let mut hasher = Groestl::new();
+        hasher.input(input);
+        hasher.finalize()
+    }
+
+    /// Finalizes the hash and returns the digest as an array.
+    pub fn finalize(self) -> [u8; OUT] {
+        let mut out = [0; OUT];
+        out.copy_from_slice(&self.inner.result());
+        out
+    }
+}
+
+impl<const OUT: usize> Digest for Groestl<OUT>
+    where OutputLen<OUT>: ValidOutputLen,
+{
+    type OutputSize =
+        <<OutputLen<OUT> as ValidOutputLen>::Hasher as Digest>::OutputSize;
+    type BlockSize =
+        <<OutputLen<OUT> as ValidOutputLen>::Hasher as Digest>::BlockSize;
+
+    fn input(&mut self, input: &[u8]) {
+        self.inner.input(input);
+    }
+
+    fn result(self) -> GenericArray<u8, Self::OutputSize> {
+        self.inner.result()
+    }
+}
@@ -0,0 +1,43 @@
+//! Adapter for using Groestl with `core::hash`.
+
+use core::hash::{BuildHasher, Hasher};
+
+use byte_tools::read_u64_be;
+use digest::Digest;
+use Groestl256;
+
+/// A `core::hash::Hasher` backed by Groestl-256.
+///
+/// `finish` returns the first 8 bytes of the digest read as a big-endian
+/// `u64`. At that width collisions are expected after about 2^32 distinct
+/// inputs, so this is suited to content addressing and experiments, not to
+/// anything that relies on Groestl's full collision resistance. It is also
+/// far slower than the hashers normally used for hash tables.
+#[derive(Clone, Debug, Default)]
+pub struct GroestlHasher {
+    groestl: Groestl256,
+}
+
+impl Hasher for GroestlHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.groestl.input(bytes);
+    }
+
+    // The hasher is only `Copy` when the `zeroize` feature is off.
+    #[allow(clippy::clone_on_copy)]
+    fn finish(&self) -> u64 {
+        read_u64_be(&self.groestl.clone().result()[..8])
+    }
+}
+
+/// Builds `GroestlHasher`s, e.g. for `HashMap::with_hasher`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BuildGroestlHasher;
+
+impl BuildHasher for BuildGroestlHasher {
+    type Hasher = GroestlHasher;
+
+    fn build_hasher(&self) -> GroestlHasher {
+        GroestlHasher::default()
+    }
+}
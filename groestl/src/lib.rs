@@ -11,6 +11,22 @@
 //! to specify a digest size between 1 and 32 inclusive, and `GroestlBig` allows
 //! you to specify a digest size between 33 and 64 inclusive.
 //!
+//! `Groestl<OUT>` takes the digest size in bytes as a const generic instead,
+//! so `Groestl<32>` is Groestl-256, and its `finalize` returns `[u8; OUT]`.
+//!
+//! The original SHA-3 submission, Groestl-0, is available for compatibility
+//! with older test suites as `Groestl0Small` and `Groestl0Big` (and the
+//! `Groestl0_224` through `Groestl0_512` aliases). It is not compatible with
+//! the final Groestl and should not be used for new applications.
+//!
+//! The crate is `#![no_std]` and never allocates. Only the `std` feature, and
+//! the `aesni`, `ssse3`, `pclmul`, `parallel` and `async-io` features which
+//! enable it, link the standard library. The `alloc` feature adds the
+//! `finalize_hex` helpers.
+//!
+//! The `digest010` feature implements the `digest` 0.10 traits, for use with
+//! current RustCrypto crates such as `hmac`; see `core_api`.
+//!
 //! ```rust
 //! use groestl::{Digest, Groestl256};
 //!
@@ -31,13 +47,92 @@
 //! hasher.input(b"my message");
 //! let result = hasher.result();
 //! ```
+//!
+//! # Thread safety
+//!
+//! Every hasher, and `GroestlVar`, `GroestlHasher` and `GroestlError`, is
+//! `Send` and `Sync`. They are plain values with no interior mutability or
+//! shared ownership, so a hasher may be moved to another thread, and a shared
+//! reference only allows reading it or cloning it. The tests check this at
+//! compile time, and new fields must keep it so.
+//!
+//! # Message length
+//!
+//! The padding records the message length as a 64-bit count of blocks, so a
+//! message may be up to 2^64 - 2 blocks long: just under 2^70 bytes with the
+//! 64-byte block and 2^71 bytes with the 128-byte block. The count is kept
+//! as a `u64` whatever the platform's `usize`, and the hasher panics rather
+//! than wrap it.
+//!
+//! # Backends
+//!
+//! The P and Q permutations have several implementations, chosen at build
+//! time through Cargo features:
+//!
+//! - `backend-scalar` (the default) works on a byte matrix exactly as the
+//!   specification describes, and is the reference to audit.
+//! - `backend-table` uses combined lookup tables. It is faster but not
+//!   constant-time, and cannot be combined with `ct-sbox`.
+//! - `backend-simd` uses AES-NI, or SSSE3 on CPUs without it, when the running
+//!   CPU supports them. Otherwise it falls back to the other enabled backends.
+//!
+//! If several are enabled, the fastest one wins. `backend-table` and
+//! `backend-simd` are shorthands for the `table` and `aesni` features.
+//!
+//! With `backend-scalar`, the `pclmul` feature computes `MixBytes` with
+//! carry-less multiplication on x86_64 CPUs that support it. It is checked
+//! against the byte-matrix multiply in the tests.
+//!
+//! For hashing secret data, as in HMAC or a KDF, the `ct-sbox` feature makes
+//! `backend-scalar` and `rows` compute `SubBytes` and `MixBytes` without
+//! lookups indexed by the state.
+//!
+//! # Tree hashing
+//!
+//! `hash_tree` and, with the `parallel` feature, `hash_parallel` hash large
+//! buffers as a tree so that the work can be spread over several cores. This
+//! is a construction of this crate, not part of the Groestl specification.
+//! The input is split into leaves of `leaf_size` bytes, the last one possibly
+//! shorter, and empty input is a single empty leaf. Each leaf is hashed after
+//! a `0x00` byte, and the root is the hash of a `0x01` byte followed by the
+//! leaf digests in order. The digest depends on `leaf_size` but not on the
+//! number of threads.
+//!
+//! Within one message, P and Q of a block could in principle run on two
+//! threads, but handing a block to another thread costs more than a
+//! permutation: measured, a wide compression took about 36 µs that way
+//! against 13 µs sequentially. The crate does not offer it, and splits work
+//! only across leaves.
 
 #![no_std]
+#[cfg(feature = "std")]
+#[macro_use]
+extern crate std;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 extern crate byte_tools;
 extern crate digest;
+#[cfg(feature = "digest010")]
+extern crate digest010;
 extern crate digest_buffer;
 extern crate generic_array;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "async-io")]
+extern crate tokio;
+#[cfg(feature = "zeroize")]
+extern crate zeroize;
+
+#[cfg(all(feature = "ct-sbox", feature = "table"))]
+compile_error!(
+    "the `table` backend indexes tables by secret state and cannot honour \
+     `ct-sbox`; disable `table` and `backend-table`"
+);
 
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 pub use digest::Digest;
 use generic_array::{ArrayLength, GenericArray};
 use generic_array::typenum::{
@@ -45,33 +140,512 @@ use generic_array::typenum::{
     U0, U28, U32, U33, U48, U64, U65, U128,
 };
 
+#[cfg(all(feature = "aesni", target_arch = "x86_64"))]
+mod aesni;
 mod consts;
+#[cfg(feature = "digest010")]
+pub mod core_api;
+mod fixed;
 mod groestl;
-mod matrix;
+mod hasher;
+pub mod matrix;
+#[cfg(all(feature = "pclmul", target_arch = "x86_64"))]
+mod pclmul;
+#[cfg(all(feature = "rows", not(feature = "table")))]
+mod rows;
+mod sbox;
+#[cfg(all(feature = "ssse3", target_arch = "x86_64"))]
+mod ssse3;
+#[cfg(feature = "table")]
+mod table;
+mod tree;
+mod var;
+
+pub use fixed::{Groestl, OutputLen, ValidOutputLen};
+pub use groestl::{
+    compress, compress_pure, output_transform, permutation_p, permutation_q,
+    ValidBlockSize, ValidOutputSize,
+};
+pub use hasher::{BuildGroestlHasher, GroestlHasher};
+pub use var::GroestlVar;
 
-#[derive(Copy, Clone, Default)]
+/// Errors from validating the arguments of hasher operations, and from
+/// `self_test`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GroestlError {
+    /// An output of `requested` bytes was asked for where at most `max` are
+    /// supported.
+    InvalidOutputLen { requested: usize, max: usize },
+    /// An output buffer, or a hex digest, of `actual` bytes was passed where
+    /// exactly `expected` are needed.
+    WrongOutputLen { expected: usize, actual: usize },
+    /// A block, such as an IV, of the given length was passed where a block
+    /// of another size was needed.
+    InvalidBlockSize(usize),
+    /// `process_bits` was given a bit length beyond the end of its input.
+    InvalidBitLength,
+    /// More output was requested than the output transformation produces,
+    /// i.e. more than one block.
+    OutputTooLong,
+    /// A hex string had an odd length or a character other than a hex digit.
+    InvalidHex,
+    /// `self_test` computed a digest other than the expected one.
+    SelfTestFailed,
+}
+
+impl core::fmt::Display for GroestlError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match *self {
+            GroestlError::InvalidOutputLen { requested, max } => write!(
+                f,
+                "output length {} is invalid (maximum {})",
+                requested, max,
+            ),
+            GroestlError::WrongOutputLen { expected, actual } => write!(
+                f,
+                "output length {} is wrong (expected {})",
+                actual, expected,
+            ),
+            GroestlError::InvalidBlockSize(len) => {
+                write!(f, "invalid block size {}", len)
+            }
+            GroestlError::InvalidBitLength => {
+                f.write_str("bit length exceeds the input")
+            }
+            GroestlError::OutputTooLong => f.write_str(
+                "requested output is longer than the Groestl block",
+            ),
+            GroestlError::InvalidHex => f.write_str("invalid hex string"),
+            GroestlError::SelfTestFailed => {
+                f.write_str("Groestl self-test failed")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GroestlError {}
+
+/// Digest sizes between 1 and 32 bytes inclusive, which use the 64-byte
+/// block. Implemented for every suitable `typenum` unsigned integer.
+pub trait SmallOutputSize: ValidOutputSize<U64> + Default {}
+
+impl<N> SmallOutputSize for N
+    where N: ArrayLength<u8> + Default + Cmp<U0> + Cmp<U33>,
+          Compare<N, U0>: Same<Greater>,
+          Compare<N, U33>: Same<Less>,
+{}
+
+/// Digest sizes between 33 and 64 bytes inclusive, which use the 128-byte
+/// block. Implemented for every suitable `typenum` unsigned integer.
+pub trait BigOutputSize: ValidOutputSize<U128> + Default {}
+
+impl<N> BigOutputSize for N
+    where N: ArrayLength<u8> + Default + Cmp<U0> + Cmp<U32> + Cmp<U65>,
+          Compare<N, U0>: Same<Greater>,
+          Compare<N, U32>: Same<Greater>,
+          Compare<N, U65>: Same<Less>,
+{}
+
+#[cfg(feature = "alloc")]
+fn to_hex(bytes: &[u8], upper: bool) -> String {
+    use core::fmt::Write;
+
+    let mut s = String::with_capacity(2 * bytes.len());
+    for b in bytes {
+        if upper {
+            write!(s, "{:02X}", b).unwrap();
+        } else {
+            write!(s, "{:02x}", b).unwrap();
+        }
+    }
+    s
+}
+
+// Decodes `hex`, in either case, into `out`, which it must fill exactly.
+fn from_hex(hex: &str, out: &mut [u8]) -> Result<(), GroestlError> {
+    let hex = hex.as_bytes();
+    if hex.len() & 1 != 0 {
+        return Err(GroestlError::InvalidHex);
+    }
+    if hex.len() / 2 != out.len() {
+        return Err(GroestlError::WrongOutputLen {
+            expected: out.len(),
+            actual: hex.len() / 2,
+        });
+    }
+    fn nibble(c: u8) -> Result<u8, GroestlError> {
+        match c {
+            b'0'..=b'9' => Ok(c - b'0'),
+            b'a'..=b'f' => Ok(c - b'a' + 10),
+            b'A'..=b'F' => Ok(c - b'A' + 10),
+            _ => Err(GroestlError::InvalidHex),
+        }
+    }
+    for (b, pair) in out.iter_mut().zip(hex.chunks(2)) {
+        *b = nibble(pair[0])? << 4 | nibble(pair[1])?;
+    }
+    Ok(())
+}
+
+// Bytes read from a file per system call in `hash_file`.
+#[cfg(feature = "std")]
+const FILE_BUFFER_SIZE: usize = 64 * 1024;
+
+// Compares without an early exit so the running time does not depend on
+// where the inputs differ. Lengths are not secret.
+fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct GroestlSmall<OutputSize>
-    where OutputSize: ArrayLength<u8> + Cmp<U0> + Cmp<U33>,
-          Compare<OutputSize, U0>: Same<Greater>,
-          Compare<OutputSize, U33>: Same<Less>,
+    where OutputSize: SmallOutputSize,
 {
     groestl: groestl::Groestl<OutputSize, U64>,
 }
 
 impl<OutputSize> GroestlSmall<OutputSize>
-    where OutputSize: ArrayLength<u8> + Cmp<U0> + Cmp<U33> + Default,
-          Compare<OutputSize, U0>: Same<Greater>,
-          Compare<OutputSize, U33>: Same<Less>,
+    where OutputSize: SmallOutputSize,
 {
+    /// Creates a hasher. The same as `default()`.
     pub fn new() -> Self {
         GroestlSmall::default()
     }
+
+    /// Computes the digest of `input` in one step, like
+    /// `new().chain(input).result()`.
+    pub fn digest(input: &[u8]) -> GenericArray<u8, OutputSize> {
+        groestl::Groestl::<OutputSize, U64>::digest(input)
+    }
+
+    /// Computes the digest of the concatenation of the chunks in `iter`.
+    pub fn digest_iter<I, B>(iter: I) -> GenericArray<u8, OutputSize>
+        where I: IntoIterator<Item = B>,
+              B: AsRef<[u8]>,
+    {
+        let mut groestl = groestl::Groestl::<OutputSize, U64>::new();
+        for chunk in iter {
+            groestl.process(chunk.as_ref());
+        }
+        groestl.finalize()
+    }
+
+    /// Computes a tree hash of `input` with leaves of `leaf_size` bytes: each
+    /// leaf is hashed on its own and the root hashes the leaf digests. This
+    /// is not standard Groestl and its digest differs from `digest`. See the
+    /// [crate documentation](index.html#tree-hashing) for the construction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_size` is zero.
+    pub fn hash_tree(
+        input: &[u8],
+        leaf_size: usize,
+    ) -> GenericArray<u8, OutputSize> {
+        tree::hash_tree::<OutputSize, U64>(input, leaf_size)
+    }
+
+    /// The same digest as `hash_tree`, with the leaves hashed on one thread
+    /// per available core.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_size` is zero.
+    #[cfg(feature = "parallel")]
+    pub fn hash_parallel(
+        input: &[u8],
+        leaf_size: usize,
+    ) -> GenericArray<u8, OutputSize> {
+        tree::hash_parallel::<OutputSize, U64>(input, leaf_size)
+    }
+
+    /// Creates a hasher that has already digested `prefix`, such as a
+    /// domain-separation tag. The same as `new()` followed by `input(prefix)`.
+    pub fn with_prefix(prefix: &[u8]) -> Self {
+        GroestlSmall { groestl: groestl::Groestl::with_prefix(prefix) }
+    }
+
+    /// Creates a hasher that starts from the chaining value `iv` instead of
+    /// the standard one derived from the output size.
+    pub fn with_iv(iv: GenericArray<u8, U64>) -> Self {
+        GroestlSmall { groestl: groestl::Groestl::with_iv(iv) }
+    }
+
+    /// `with_iv` from a plain byte slice, which must be 64 bytes long.
+    pub fn with_iv_bytes(iv: &[u8]) -> Result<Self, GroestlError> {
+        if iv.len() != 64 {
+            return Err(GroestlError::InvalidBlockSize(iv.len()));
+        }
+        Ok(Self::with_iv(GenericArray::clone_from_slice(iv)))
+    }
+
+    /// Creates a hasher whose permutations run `rounds` rounds instead of
+    /// the standard 10.
+    ///
+    /// This is not Groestl: the digests are only of use for studying
+    /// reduced-round variants and must never be relied on for security.
+    /// `reset` keeps the round count.
+    #[cfg(feature = "research")]
+    pub fn with_rounds(rounds: u8) -> Self {
+        GroestlSmall { groestl: groestl::Groestl::with_rounds(rounds) }
+    }
+
+    /// Size of the digest in bytes.
+    pub fn output_size(&self) -> usize {
+        self.groestl.output_size()
+    }
+
+    /// Size of the internal block in bytes.
+    pub fn block_size(&self) -> usize {
+        self.groestl.block_size()
+    }
+
+    /// Number of rounds of each permutation.
+    pub fn rounds(&self) -> u8 {
+        self.groestl.rounds()
+    }
+
+    /// Number of blocks compressed so far. Input still waiting in the
+    /// internal buffer for a full block is not counted.
+    pub fn blocks_processed(&self) -> u64 {
+        self.groestl.blocks_processed()
+    }
+
+    /// Number of bytes of input so far, including those still buffered, e.g.
+    /// to check a download against its content length. Saturates at
+    /// `u64::MAX`.
+    pub fn bytes_hashed(&self) -> u64 {
+        self.groestl.bytes_hashed()
+    }
+
+    /// The current chaining value, for comparing against the intermediate
+    /// values published with the reference implementation.
+    #[cfg(feature = "trace")]
+    pub fn chaining_value(&self) -> &GenericArray<u8, U64> {
+        self.groestl.chaining_value()
+    }
+
+    /// Digests `input`, calling `f` after each block is compressed with the
+    /// number of blocks so far and the new chaining value.
+    #[cfg(feature = "trace")]
+    pub fn process_observed<F>(&mut self, input: &[u8], f: F)
+        where F: FnMut(u64, &GenericArray<u8, U64>),
+    {
+        self.groestl.process_observed(input, f);
+    }
+
+    /// Retrieves the digest, calling `f` after each padding block in the
+    /// same way as `process_observed`.
+    #[cfg(feature = "trace")]
+    pub fn finalize_observed<F>(self, f: F) -> GenericArray<u8, OutputSize>
+        where F: FnMut(u64, &GenericArray<u8, U64>),
+    {
+        self.groestl.finalize_observed(f)
+    }
+
+    /// Digests `data`, returning the hasher so that calls can be chained.
+    pub fn update<T: AsRef<[u8]>>(&mut self, data: T) -> &mut Self {
+        self.groestl.process(data.as_ref());
+        self
+    }
+
+    /// Digests `data` and returns the hasher, for building a digest in a
+    /// single expression.
+    pub fn chain<T: AsRef<[u8]>>(mut self, data: T) -> Self {
+        self.groestl.process(data.as_ref());
+        self
+    }
+
+    /// Digests each of `bufs` in order, as if they had been concatenated,
+    /// for data spread over scatter-gather buffers.
+    pub fn process_vectored(&mut self, bufs: &[&[u8]]) {
+        self.groestl.process_vectored(bufs);
+    }
+
+    /// Digests `input` in chunks of up to 64 KiB, calling `cb` with the
+    /// number of bytes digested so far after each one.
+    pub fn process_with_progress<F>(&mut self, input: &[u8], cb: F)
+        where F: FnMut(usize),
+    {
+        self.groestl.process_with_progress(input, cb);
+    }
+
+    /// Digests whole blocks without copying them into the internal buffer,
+    /// for input that is already split into blocks, such as a memory-mapped
+    /// file.
+    ///
+    /// The input so far must be a multiple of the block size. Any tail that
+    /// does not fill a block is passed to `input` afterwards, before the hash
+    /// is finalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if earlier input left a partial block in the buffer.
+    pub fn process_blocks(&mut self, blocks: &[GenericArray<u8, U64>]) {
+        self.groestl.process_blocks(blocks);
+    }
+
+    /// Digests the first `bit_len` bits of `input`, most significant bit of
+    /// each byte first. A trailing partial byte must be the last input, and
+    /// its unused low bits are ignored.
+    ///
+    /// Fails without digesting anything if `bit_len` exceeds the bits in
+    /// `input`.
+    pub fn process_bits(
+        &mut self,
+        input: &[u8],
+        bit_len: usize,
+    ) -> Result<(), GroestlError> {
+        self.groestl.process_bits(input, bit_len)
+    }
+
+    /// Finalizes the hash and writes the last `out.len()` bytes of the
+    /// output transformation to `out`, for digest lengths without a `typenum`
+    /// type. Fails if `out` is longer than the block.
+    ///
+    /// Note that the IV still encodes `OutputSize`, so this is not the same
+    /// as a Groestl instance defined with `out.len()` as its output size.
+    pub fn finalize_truncated(
+        self,
+        out: &mut [u8],
+    ) -> Result<(), GroestlError> {
+        self.groestl.finalize_truncated(out)
+    }
+
+    /// Finalizes the hash and copies it into `out`, which must be exactly
+    /// `output_size()` bytes long.
+    pub fn finalize_slice(
+        self,
+        out: &mut [u8],
+    ) -> Result<(), GroestlError> {
+        self.groestl.finalize_slice(out)
+    }
+
+    /// Finalizes the hash and compares it with `expected` in constant time.
+    /// Returns `false` if the lengths differ.
+    pub fn verify(self, expected: &[u8]) -> bool {
+        ct_eq(&self.groestl.finalize(), expected)
+    }
+
+    /// Finalizes the hash and compares it in constant time with
+    /// `expected_hex`, the digest in hex of either case. Fails if the string
+    /// is not hex or does not encode exactly `output_size()` bytes.
+    pub fn verify_hex(self, expected_hex: &str) -> Result<bool, GroestlError> {
+        let mut expected = GenericArray::<u8, OutputSize>::default();
+        from_hex(expected_hex, &mut expected)?;
+        Ok(self.verify(&expected))
+    }
+
+    /// Reads `r` to the end into the hasher and returns the digest.
+    /// Reads interrupted by a signal are retried.
+    #[cfg(feature = "std")]
+    pub fn digest_reader<R: std::io::Read>(
+        mut self,
+        mut r: R,
+    ) -> std::io::Result<GenericArray<u8, OutputSize>> {
+        let mut buf = [0u8; 4096];
+        loop {
+            match r.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => self.groestl.process(&buf[..n]),
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(self.groestl.finalize())
+    }
+
+    /// Computes the digest of the file at `path`. The file is read through
+    /// a 64 KiB buffer, so a large file takes one system call per 64 KiB
+    /// rather than per 4 KiB chunk of `digest_reader`.
+    #[cfg(feature = "std")]
+    pub fn hash_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> std::io::Result<GenericArray<u8, OutputSize>> {
+        let file = std::fs::File::open(path)?;
+        Self::new().digest_reader(
+            std::io::BufReader::with_capacity(FILE_BUFFER_SIZE, file),
+        )
+    }
+
+    /// Finalizes the hash and returns it as lowercase hex.
+    #[cfg(feature = "alloc")]
+    pub fn finalize_hex(self) -> String {
+        to_hex(&self.groestl.finalize(), false)
+    }
+
+    /// Finalizes the hash and returns it as uppercase hex.
+    #[cfg(feature = "alloc")]
+    pub fn finalize_hex_upper(self) -> String {
+        to_hex(&self.groestl.finalize(), true)
+    }
+
+    /// Resets the hasher to the state `new()` returns so it can be reused.
+    /// This restarts from the standard IV: a custom IV from `with_iv` or
+    /// `rekey`, and a `with_prefix` prefix, are discarded, and only a
+    /// `with_rounds` round count is kept. Use `reset_with_iv` to restart
+    /// from a custom IV.
+    pub fn reset(&mut self) {
+        self.groestl.reset();
+    }
+
+    /// Discards any input and restarts from the chaining value `iv`, as if
+    /// the hasher had just been created with `with_iv(iv)`. Handy for
+    /// hashing each message of a PRF under its own key-derived IV.
+    pub fn rekey(&mut self, iv: GenericArray<u8, U64>) {
+        self.groestl.rekey(iv);
+    }
+
+    /// The same as `rekey`, borrowing the IV so that a caller looping over
+    /// precomputed IVs need not copy each one first. The round count is
+    /// kept.
+    pub fn reset_with_iv(&mut self, iv: &GenericArray<u8, U64>) {
+        self.groestl.reset_with_iv(iv);
+    }
+
+    /// The blocks that finalizing would still compress, padding included,
+    /// for comparing the padding against a reference. Only the input since
+    /// the last full block is left, so this is one or two blocks.
+    #[cfg(feature = "alloc")]
+    pub fn padded_blocks(self) -> Vec<GenericArray<u8, U64>> {
+        self.groestl.padded_blocks()
+    }
+
+    /// Retrieves the digest and resets the hasher, without consuming it.
+    /// Afterwards the hasher is in the state `reset` leaves it in, with the
+    /// standard IV.
+    pub fn finalize_reset(&mut self) -> GenericArray<u8, OutputSize> {
+        self.groestl.finalize_reset()
+    }
+
+    /// Returns the digest of the input so far and leaves the hasher as it
+    /// was, so more input can follow. This copies the whole hasher,
+    /// including its block buffer.
+    pub fn clone_finalize(&self) -> GenericArray<u8, OutputSize> {
+        self.groestl.clone_finalize()
+    }
+
+    /// Returns an independent copy of the hasher, buffered input included,
+    /// so that a shared prefix can be hashed once and continued with
+    /// different suffixes. The same as `clone()`.
+    // Not `Copy` with the `zeroize` feature.
+    #[allow(clippy::clone_on_copy)]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
 }
 
 impl<OutputSize> Digest for GroestlSmall<OutputSize>
-    where OutputSize: ArrayLength<u8> + Cmp<U0> + Cmp<U33>,
-          Compare<OutputSize, U0>: Same<Greater>,
-          Compare<OutputSize, U33>: Same<Less>,
+    where OutputSize: SmallOutputSize,
 {
     type OutputSize = OutputSize;
     type BlockSize = U64;
@@ -85,29 +659,579 @@ impl<OutputSize> Digest for GroestlSmall<OutputSize>
     }
 }
 
-#[derive(Copy, Clone, Default)]
+impl<OutputSize> Extend<u8> for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.groestl.extend(iter);
+    }
+}
+
+impl<'a, OutputSize> Extend<&'a [u8]> for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+        self.groestl.extend(iter);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<OutputSize> std::io::Write for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.groestl.process(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes the whole buffer inside `poll_write`, which never returns
+/// `Pending`. Hashing is CPU-bound, so one large write holds up the executor
+/// for as long as it takes; feed big inputs in chunks of a few KiB.
+#[cfg(feature = "async-io")]
+impl<OutputSize> tokio::io::AsyncWrite for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize + Unpin,
+{
+    fn poll_write(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context,
+        buf: &[u8],
+    ) -> core::task::Poll<std::io::Result<usize>> {
+        self.get_mut().groestl.process(buf);
+        core::task::Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<OutputSize> serde::Serialize for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer,
+    {
+        self.groestl.serialize(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, OutputSize> serde::Deserialize<'de> for GroestlSmall<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de>,
+    {
+        groestl::Groestl::deserialize(d)
+            .map(|groestl| GroestlSmall { groestl })
+    }
+}
+
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct GroestlBig<OutputSize>
-    where OutputSize: ArrayLength<u8> + Cmp<U32> + Cmp<U65>,
-          Compare<OutputSize, U32>: Same<Greater>,
-          Compare<OutputSize, U65>: Same<Less>,
+    where OutputSize: BigOutputSize,
 {
     groestl: groestl::Groestl<OutputSize, U128>,
 }
 
 impl<OutputSize> GroestlBig<OutputSize>
-    where OutputSize: ArrayLength<u8> + Cmp<U32> + Cmp<U65> + Default,
-          Compare<OutputSize, U32>: Same<Greater>,
-          Compare<OutputSize, U65>: Same<Less>,
+    where OutputSize: BigOutputSize,
 {
+    /// Creates a hasher. The same as `default()`.
     pub fn new() -> Self {
         GroestlBig::default()
     }
+
+    /// Computes the digest of `input` in one step, like
+    /// `new().chain(input).result()`.
+    pub fn digest(input: &[u8]) -> GenericArray<u8, OutputSize> {
+        groestl::Groestl::<OutputSize, U128>::digest(input)
+    }
+
+    /// Computes the digest of the concatenation of the chunks in `iter`.
+    pub fn digest_iter<I, B>(iter: I) -> GenericArray<u8, OutputSize>
+        where I: IntoIterator<Item = B>,
+              B: AsRef<[u8]>,
+    {
+        let mut groestl = groestl::Groestl::<OutputSize, U128>::new();
+        for chunk in iter {
+            groestl.process(chunk.as_ref());
+        }
+        groestl.finalize()
+    }
+
+    /// Computes a tree hash of `input` with leaves of `leaf_size` bytes: each
+    /// leaf is hashed on its own and the root hashes the leaf digests. This
+    /// is not standard Groestl and its digest differs from `digest`. See the
+    /// [crate documentation](index.html#tree-hashing) for the construction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_size` is zero.
+    pub fn hash_tree(
+        input: &[u8],
+        leaf_size: usize,
+    ) -> GenericArray<u8, OutputSize> {
+        tree::hash_tree::<OutputSize, U128>(input, leaf_size)
+    }
+
+    /// The same digest as `hash_tree`, with the leaves hashed on one thread
+    /// per available core.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_size` is zero.
+    #[cfg(feature = "parallel")]
+    pub fn hash_parallel(
+        input: &[u8],
+        leaf_size: usize,
+    ) -> GenericArray<u8, OutputSize> {
+        tree::hash_parallel::<OutputSize, U128>(input, leaf_size)
+    }
+
+    /// Creates a hasher that has already digested `prefix`, such as a
+    /// domain-separation tag. The same as `new()` followed by `input(prefix)`.
+    pub fn with_prefix(prefix: &[u8]) -> Self {
+        GroestlBig { groestl: groestl::Groestl::with_prefix(prefix) }
+    }
+
+    /// Creates a hasher that starts from the chaining value `iv` instead of
+    /// the standard one derived from the output size.
+    pub fn with_iv(iv: GenericArray<u8, U128>) -> Self {
+        GroestlBig { groestl: groestl::Groestl::with_iv(iv) }
+    }
+
+    /// `with_iv` from a plain byte slice, which must be 128 bytes long.
+    pub fn with_iv_bytes(iv: &[u8]) -> Result<Self, GroestlError> {
+        if iv.len() != 128 {
+            return Err(GroestlError::InvalidBlockSize(iv.len()));
+        }
+        Ok(Self::with_iv(GenericArray::clone_from_slice(iv)))
+    }
+
+    /// Creates a hasher whose permutations run `rounds` rounds instead of
+    /// the standard 14.
+    ///
+    /// This is not Groestl: the digests are only of use for studying
+    /// reduced-round variants and must never be relied on for security.
+    /// `reset` keeps the round count.
+    #[cfg(feature = "research")]
+    pub fn with_rounds(rounds: u8) -> Self {
+        GroestlBig { groestl: groestl::Groestl::with_rounds(rounds) }
+    }
+
+    /// Size of the digest in bytes.
+    pub fn output_size(&self) -> usize {
+        self.groestl.output_size()
+    }
+
+    /// Size of the internal block in bytes.
+    pub fn block_size(&self) -> usize {
+        self.groestl.block_size()
+    }
+
+    /// Number of rounds of each permutation.
+    pub fn rounds(&self) -> u8 {
+        self.groestl.rounds()
+    }
+
+    /// Number of blocks compressed so far. Input still waiting in the
+    /// internal buffer for a full block is not counted.
+    pub fn blocks_processed(&self) -> u64 {
+        self.groestl.blocks_processed()
+    }
+
+    /// Number of bytes of input so far, including those still buffered, e.g.
+    /// to check a download against its content length. Saturates at
+    /// `u64::MAX`.
+    pub fn bytes_hashed(&self) -> u64 {
+        self.groestl.bytes_hashed()
+    }
+
+    /// The current chaining value, for comparing against the intermediate
+    /// values published with the reference implementation.
+    #[cfg(feature = "trace")]
+    pub fn chaining_value(&self) -> &GenericArray<u8, U128> {
+        self.groestl.chaining_value()
+    }
+
+    /// Digests `input`, calling `f` after each block is compressed with the
+    /// number of blocks so far and the new chaining value.
+    #[cfg(feature = "trace")]
+    pub fn process_observed<F>(&mut self, input: &[u8], f: F)
+        where F: FnMut(u64, &GenericArray<u8, U128>),
+    {
+        self.groestl.process_observed(input, f);
+    }
+
+    /// Retrieves the digest, calling `f` after each padding block in the
+    /// same way as `process_observed`.
+    #[cfg(feature = "trace")]
+    pub fn finalize_observed<F>(self, f: F) -> GenericArray<u8, OutputSize>
+        where F: FnMut(u64, &GenericArray<u8, U128>),
+    {
+        self.groestl.finalize_observed(f)
+    }
+
+    /// Digests `data`, returning the hasher so that calls can be chained.
+    pub fn update<T: AsRef<[u8]>>(&mut self, data: T) -> &mut Self {
+        self.groestl.process(data.as_ref());
+        self
+    }
+
+    /// Digests `data` and returns the hasher, for building a digest in a
+    /// single expression.
+    pub fn chain<T: AsRef<[u8]>>(mut self, data: T) -> Self {
+        self.groestl.process(data.as_ref());
+        self
+    }
+
+    /// Digests each of `bufs` in order, as if they had been concatenated,
+    /// for data spread over scatter-gather buffers.
+    pub fn process_vectored(&mut self, bufs: &[&[u8]]) {
+        self.groestl.process_vectored(bufs);
+    }
+
+    /// Digests `input` in chunks of up to 64 KiB, calling `cb` with the
+    /// number of bytes digested so far after each one.
+    pub fn process_with_progress<F>(&mut self, input: &[u8], cb: F)
+        where F: FnMut(usize),
+    {
+        self.groestl.process_with_progress(input, cb);
+    }
+
+    /// Digests whole blocks without copying them into the internal buffer,
+    /// for input that is already split into blocks, such as a memory-mapped
+    /// file.
+    ///
+    /// The input so far must be a multiple of the block size. Any tail that
+    /// does not fill a block is passed to `input` afterwards, before the hash
+    /// is finalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if earlier input left a partial block in the buffer.
+    pub fn process_blocks(&mut self, blocks: &[GenericArray<u8, U128>]) {
+        self.groestl.process_blocks(blocks);
+    }
+
+    /// Digests the first `bit_len` bits of `input`, most significant bit of
+    /// each byte first. A trailing partial byte must be the last input, and
+    /// its unused low bits are ignored.
+    ///
+    /// Fails without digesting anything if `bit_len` exceeds the bits in
+    /// `input`.
+    pub fn process_bits(
+        &mut self,
+        input: &[u8],
+        bit_len: usize,
+    ) -> Result<(), GroestlError> {
+        self.groestl.process_bits(input, bit_len)
+    }
+
+    /// Finalizes the hash and writes the last `out.len()` bytes of the
+    /// output transformation to `out`, for digest lengths without a `typenum`
+    /// type. Fails if `out` is longer than the block.
+    ///
+    /// Note that the IV still encodes `OutputSize`, so this is not the same
+    /// as a Groestl instance defined with `out.len()` as its output size.
+    pub fn finalize_truncated(
+        self,
+        out: &mut [u8],
+    ) -> Result<(), GroestlError> {
+        self.groestl.finalize_truncated(out)
+    }
+
+    /// Finalizes the hash and copies it into `out`, which must be exactly
+    /// `output_size()` bytes long.
+    pub fn finalize_slice(
+        self,
+        out: &mut [u8],
+    ) -> Result<(), GroestlError> {
+        self.groestl.finalize_slice(out)
+    }
+
+    /// Finalizes the hash and compares it with `expected` in constant time.
+    /// Returns `false` if the lengths differ.
+    pub fn verify(self, expected: &[u8]) -> bool {
+        ct_eq(&self.groestl.finalize(), expected)
+    }
+
+    /// Finalizes the hash and compares it in constant time with
+    /// `expected_hex`, the digest in hex of either case. Fails if the string
+    /// is not hex or does not encode exactly `output_size()` bytes.
+    pub fn verify_hex(self, expected_hex: &str) -> Result<bool, GroestlError> {
+        let mut expected = GenericArray::<u8, OutputSize>::default();
+        from_hex(expected_hex, &mut expected)?;
+        Ok(self.verify(&expected))
+    }
+
+    /// Reads `r` to the end into the hasher and returns the digest.
+    /// Reads interrupted by a signal are retried.
+    #[cfg(feature = "std")]
+    pub fn digest_reader<R: std::io::Read>(
+        mut self,
+        mut r: R,
+    ) -> std::io::Result<GenericArray<u8, OutputSize>> {
+        let mut buf = [0u8; 4096];
+        loop {
+            match r.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => self.groestl.process(&buf[..n]),
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(self.groestl.finalize())
+    }
+
+    /// Computes the digest of the file at `path`. The file is read through
+    /// a 64 KiB buffer, so a large file takes one system call per 64 KiB
+    /// rather than per 4 KiB chunk of `digest_reader`.
+    #[cfg(feature = "std")]
+    pub fn hash_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> std::io::Result<GenericArray<u8, OutputSize>> {
+        let file = std::fs::File::open(path)?;
+        Self::new().digest_reader(
+            std::io::BufReader::with_capacity(FILE_BUFFER_SIZE, file),
+        )
+    }
+
+    /// Finalizes the hash and returns it as lowercase hex.
+    #[cfg(feature = "alloc")]
+    pub fn finalize_hex(self) -> String {
+        to_hex(&self.groestl.finalize(), false)
+    }
+
+    /// Finalizes the hash and returns it as uppercase hex.
+    #[cfg(feature = "alloc")]
+    pub fn finalize_hex_upper(self) -> String {
+        to_hex(&self.groestl.finalize(), true)
+    }
+
+    /// Resets the hasher to the state `new()` returns so it can be reused.
+    /// This restarts from the standard IV: a custom IV from `with_iv` or
+    /// `rekey`, and a `with_prefix` prefix, are discarded, and only a
+    /// `with_rounds` round count is kept. Use `reset_with_iv` to restart
+    /// from a custom IV.
+    pub fn reset(&mut self) {
+        self.groestl.reset();
+    }
+
+    /// Discards any input and restarts from the chaining value `iv`, as if
+    /// the hasher had just been created with `with_iv(iv)`. Handy for
+    /// hashing each message of a PRF under its own key-derived IV.
+    pub fn rekey(&mut self, iv: GenericArray<u8, U128>) {
+        self.groestl.rekey(iv);
+    }
+
+    /// The same as `rekey`, borrowing the IV so that a caller looping over
+    /// precomputed IVs need not copy each one first. The round count is
+    /// kept.
+    pub fn reset_with_iv(&mut self, iv: &GenericArray<u8, U128>) {
+        self.groestl.reset_with_iv(iv);
+    }
+
+    /// The blocks that finalizing would still compress, padding included,
+    /// for comparing the padding against a reference. Only the input since
+    /// the last full block is left, so this is one or two blocks.
+    #[cfg(feature = "alloc")]
+    pub fn padded_blocks(self) -> Vec<GenericArray<u8, U128>> {
+        self.groestl.padded_blocks()
+    }
+
+    /// Retrieves the digest and resets the hasher, without consuming it.
+    /// Afterwards the hasher is in the state `reset` leaves it in, with the
+    /// standard IV.
+    pub fn finalize_reset(&mut self) -> GenericArray<u8, OutputSize> {
+        self.groestl.finalize_reset()
+    }
+
+    /// Returns the digest of the input so far and leaves the hasher as it
+    /// was, so more input can follow. This copies the whole hasher,
+    /// including its block buffer.
+    pub fn clone_finalize(&self) -> GenericArray<u8, OutputSize> {
+        self.groestl.clone_finalize()
+    }
+
+    /// Returns an independent copy of the hasher, buffered input included,
+    /// so that a shared prefix can be hashed once and continued with
+    /// different suffixes. The same as `clone()`.
+    // Not `Copy` with the `zeroize` feature.
+    #[allow(clippy::clone_on_copy)]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
 }
 
 impl<OutputSize> Digest for GroestlBig<OutputSize>
-    where OutputSize: ArrayLength<u8> + Cmp<U32> + Cmp<U65>,
-          Compare<OutputSize, U32>: Same<Greater>,
-          Compare<OutputSize, U65>: Same<Less>,
+    where OutputSize: BigOutputSize,
+{
+    type OutputSize = OutputSize;
+    type BlockSize = U128;
+
+    fn input(&mut self, input: &[u8]) {
+        self.groestl.process(input);
+    }
+
+    fn result(self) -> GenericArray<u8, Self::OutputSize> {
+        self.groestl.finalize()
+    }
+}
+
+impl<OutputSize> Extend<u8> for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.groestl.extend(iter);
+    }
+}
+
+impl<'a, OutputSize> Extend<&'a [u8]> for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+        self.groestl.extend(iter);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<OutputSize> std::io::Write for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.groestl.process(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes the whole buffer inside `poll_write`, which never returns
+/// `Pending`. Hashing is CPU-bound, so one large write holds up the executor
+/// for as long as it takes; feed big inputs in chunks of a few KiB.
+#[cfg(feature = "async-io")]
+impl<OutputSize> tokio::io::AsyncWrite for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize + Unpin,
+{
+    fn poll_write(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context,
+        buf: &[u8],
+    ) -> core::task::Poll<std::io::Result<usize>> {
+        self.get_mut().groestl.process(buf);
+        core::task::Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: core::pin::Pin<&mut Self>,
+        _cx: &mut core::task::Context,
+    ) -> core::task::Poll<std::io::Result<()>> {
+        core::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<OutputSize> serde::Serialize for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: serde::Serializer,
+    {
+        self.groestl.serialize(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, OutputSize> serde::Deserialize<'de> for GroestlBig<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de>,
+    {
+        groestl::Groestl::deserialize(d)
+            .map(|groestl| GroestlBig { groestl })
+    }
+}
+
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Groestl0Small<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    groestl: groestl::Groestl<OutputSize, U64, groestl::Original>,
+}
+
+impl<OutputSize> Groestl0Small<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    /// Creates a hasher. The same as `default()`.
+    pub fn new() -> Self {
+        Groestl0Small::default()
+    }
+}
+
+impl<OutputSize> Digest for Groestl0Small<OutputSize>
+    where OutputSize: SmallOutputSize,
+{
+    type OutputSize = OutputSize;
+    type BlockSize = U64;
+
+    fn input(&mut self, input: &[u8]) {
+        self.groestl.process(input);
+    }
+
+    fn result(self) -> GenericArray<u8, Self::OutputSize> {
+        self.groestl.finalize()
+    }
+}
+
+#[cfg_attr(not(feature = "zeroize"), derive(Copy))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Groestl0Big<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    groestl: groestl::Groestl<OutputSize, U128, groestl::Original>,
+}
+
+impl<OutputSize> Groestl0Big<OutputSize>
+    where OutputSize: BigOutputSize,
+{
+    /// Creates a hasher. The same as `default()`.
+    pub fn new() -> Self {
+        Groestl0Big::default()
+    }
+}
+
+impl<OutputSize> Digest for Groestl0Big<OutputSize>
+    where OutputSize: BigOutputSize,
 {
     type OutputSize = OutputSize;
     type BlockSize = U128;
@@ -121,7 +1245,75 @@ impl<OutputSize> Digest for GroestlBig<OutputSize>
     }
 }
 
+/// Groestl-224, producing a 224-bit (28 byte) digest.
 pub type Groestl224 = GroestlSmall<U28>;
+/// Groestl-256, producing a 256-bit (32 byte) digest.
 pub type Groestl256 = GroestlSmall<U32>;
+/// Groestl-384, producing a 384-bit (48 byte) digest.
 pub type Groestl384 = GroestlBig<U48>;
+/// Groestl-512, producing a 512-bit (64 byte) digest.
 pub type Groestl512 = GroestlBig<U64>;
+
+/// Groestl-0-224, the original submission's 224-bit digest.
+#[allow(non_camel_case_types)]
+pub type Groestl0_224 = Groestl0Small<U28>;
+/// Groestl-0-256, the original submission's 256-bit digest.
+#[allow(non_camel_case_types)]
+pub type Groestl0_256 = Groestl0Small<U32>;
+/// Groestl-0-384, the original submission's 384-bit digest.
+#[allow(non_camel_case_types)]
+pub type Groestl0_384 = Groestl0Big<U48>;
+/// Groestl-0-512, the original submission's 512-bit digest.
+#[allow(non_camel_case_types)]
+pub type Groestl0_512 = Groestl0Big<U64>;
+
+/// Computes the Groestl-224 digest of `input` in one step.
+pub fn groestl224(input: &[u8]) -> GenericArray<u8, U28> {
+    groestl::Groestl::<U28, U64>::digest(input)
+}
+
+/// Computes the Groestl-256 digest of `input` in one step.
+pub fn groestl256(input: &[u8]) -> GenericArray<u8, U32> {
+    groestl::Groestl::<U32, U64>::digest(input)
+}
+
+/// Computes the Groestl-384 digest of `input` in one step.
+pub fn groestl384(input: &[u8]) -> GenericArray<u8, U48> {
+    groestl::Groestl::<U48, U128>::digest(input)
+}
+
+/// Computes the Groestl-512 digest of `input` in one step.
+pub fn groestl512(input: &[u8]) -> GenericArray<u8, U64> {
+    groestl::Groestl::<U64, U128>::digest(input)
+}
+
+/// Checks Groestl-256 and Groestl-512 against known answers, e.g. as a
+/// power-on self-test. The backend selected at build and run time is the one
+/// tested, so this also catches a miscompiled or misdetected SIMD or table
+/// backend.
+pub fn self_test() -> Result<(), GroestlError> {
+    const MSG: &[u8] = b"The quick brown fox jumps over the lazy dog";
+    const EXPECTED_256: [u8; 32] = [
+        0x8c, 0x7a, 0xd6, 0x2e, 0xb2, 0x6a, 0x21, 0x29,
+        0x7b, 0xc3, 0x9c, 0x2d, 0x72, 0x93, 0xb4, 0xbd,
+        0x4d, 0x33, 0x99, 0xfa, 0x8a, 0xfa, 0xb2, 0x9e,
+        0x97, 0x04, 0x71, 0x73, 0x9e, 0x28, 0xb3, 0x01,
+    ];
+    const EXPECTED_512: [u8; 64] = [
+        0xba, 0xdc, 0x1f, 0x70, 0xcc, 0xd6, 0x9e, 0x0c,
+        0xf3, 0x76, 0x0c, 0x3f, 0x93, 0x88, 0x42, 0x89,
+        0xda, 0x84, 0xec, 0x13, 0xc7, 0x0b, 0x3d, 0x12,
+        0xa5, 0x3a, 0x7a, 0x8a, 0x4a, 0x51, 0x3f, 0x99,
+        0x71, 0x5d, 0x46, 0x28, 0x8f, 0x55, 0xe1, 0xdb,
+        0xf9, 0x26, 0xe6, 0xd0, 0x84, 0xa0, 0x53, 0x8e,
+        0x4e, 0xeb, 0xfc, 0x91, 0xcf, 0x2b, 0x21, 0x45,
+        0x29, 0x21, 0xcc, 0xde, 0x91, 0x31, 0x71, 0x8d,
+    ];
+
+    if groestl256(MSG)[..] != EXPECTED_256[..]
+        || groestl512(MSG)[..] != EXPECTED_512[..]
+    {
+        return Err(GroestlError::SelfTestFailed);
+    }
+    Ok(())
+}
@@ -1,9 +1,47 @@
+//! Byte matrices over GF(2^8). Groestl (like AES) reduces by the polynomial
+//! `x^8 + x^4 + x^3 + x + 1`; other fields can be plugged in through `Field`.
+
 use core::ops::{Index, IndexMut};
 
 use generic_array::{ArrayLength, GenericArray};
+use sbox::gf_mul;
+
+/// A representation of GF(2^8) as polynomials over GF(2) modulo an
+/// irreducible polynomial of degree 8.
+pub trait Field {
+    /// The reduction polynomial without its `x^8` term, e.g. `0x1b` for
+    /// `0x11b`.
+    const POLY: u8;
+
+    /// Multiplies `a` and `b` in this field.
+    fn mul(mut a: u8, mut b: u8) -> u8 {
+        let mut r = 0;
+        for _ in 0..8 {
+            r ^= a & (b & 1).wrapping_neg();
+            let carry = (a >> 7).wrapping_neg();
+            a = (a << 1) ^ (carry & Self::POLY);
+            b >>= 1;
+        }
+        r
+    }
+}
+
+/// The AES field, reduced by `0x11b`. Groestl always uses this one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Aes;
+
+impl Field for Aes {
+    const POLY: u8 = 0x1b;
+
+    fn mul(a: u8, b: u8) -> u8 {
+        gf_mul(a, b)
+    }
+}
 
-#[derive(Debug, Eq, PartialEq)]
+/// A byte matrix with `R` rows and `C` columns, indexed as `m[row][col]`.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Matrix<R: ArrayLength<GenericArray<u8, C>>, C: ArrayLength<u8>> {
+    /// The rows of the matrix.
     pub state: GenericArray<GenericArray<u8, C>, R>,
 }
 
@@ -36,47 +74,230 @@ impl<R, C> IndexMut<usize> for Matrix<R, C>
     }
 }
 
-fn poly_mul(a: u8, b: usize) -> usize {
-    let mut val = match a {
-        2 => b << 1,
-        3 => b ^ poly_mul(2, b),
-        4 => b << 2,
-        5 => b ^ poly_mul(4, b),
-        7 => b ^ poly_mul(2, b) ^ poly_mul(4, b),
-        _ => unreachable!(),
-    };
-
-    if val >= 512 {
-        val ^= 0x11b << 1;
-    }
-    if val >= 256 {
-        val ^= 0x11b;
-    }
-    val
-}
-
 impl<R, C> Matrix<R, C>
     where R: ArrayLength<GenericArray<u8, C>>,
           C: ArrayLength<u8>,
 {
+    /// Number of rows.
     pub fn rows(&self) -> usize {
         R::to_usize()
     }
 
+    /// Number of columns.
     pub fn cols(&self) -> usize {
         C::to_usize()
     }
 
+    /// Multiplies the 8-row matrix `self` on the left by the 8x8 matrix `a`
+    /// over the AES field, as Groestl's `MixBytes` does with its circulant
+    /// matrix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` has fewer than 8 rows.
     pub fn mul_array(&self, a: &[[u8; 8]; 8]) -> Self {
+        self.mul_array_in::<Aes>(a)
+    }
+
+    /// `mul_array` over the field `F`.
+    pub fn mul_array_in<F: Field>(&self, a: &[[u8; 8]; 8]) -> Self {
         let mut res = Matrix::default();
         for i in 0..8 {
             for j in 0..self.cols() {
                 for k in 0..8 {
-                    res[i][j] ^= poly_mul(a[i][k], self[k][j] as usize) as u8;
+                    res[i][j] ^= F::mul(a[i][k], self[k][j]);
                 }
             }
         }
 
         res
     }
+
+    /// Matrix product `self * rhs` over the AES field.
+    ///
+    /// ```rust
+    /// # extern crate generic_array;
+    /// # extern crate groestl;
+    /// use generic_array::typenum::U2;
+    /// use groestl::matrix::Matrix;
+    ///
+    /// # fn main() {
+    /// let mut a = Matrix::<U2, U2>::default();
+    /// a[0][0] = 2; a[0][1] = 3;
+    /// a[1][0] = 1; a[1][1] = 1;
+    /// let mut b = Matrix::<U2, U2>::default();
+    /// b[0][0] = 0x80; b[1][1] = 1;
+    ///
+    /// let c = a.mul(&b);
+    /// // 2 * 0x80 wraps around the field polynomial to 0x1b.
+    /// assert_eq!(&c[0][..], &[0x1b, 3]);
+    /// assert_eq!(&c[1][..], &[0x80, 1]);
+    /// # }
+    /// ```
+    pub fn mul<K>(&self, rhs: &Matrix<C, K>) -> Matrix<R, K>
+        where R: ArrayLength<GenericArray<u8, K>>,
+              C: ArrayLength<GenericArray<u8, K>>,
+              K: ArrayLength<u8>,
+    {
+        self.mul_in::<Aes, K>(rhs)
+    }
+
+    /// Matrix product `self * rhs` over the field `F`.
+    pub fn mul_in<F, K>(&self, rhs: &Matrix<C, K>) -> Matrix<R, K>
+        where F: Field,
+              R: ArrayLength<GenericArray<u8, K>>,
+              C: ArrayLength<GenericArray<u8, K>>,
+              K: ArrayLength<u8>,
+    {
+        let mut res = Matrix::<R, K>::default();
+        for i in 0..self.rows() {
+            for j in 0..rhs.cols() {
+                for k in 0..self.cols() {
+                    res[i][j] ^= F::mul(self[i][k], rhs[k][j]);
+                }
+            }
+        }
+        res
+    }
+
+    /// Returns the transposed matrix.
+    pub fn transpose(&self) -> Matrix<C, R>
+        where C: ArrayLength<GenericArray<u8, R>>,
+              R: ArrayLength<u8>,
+    {
+        let mut res = Matrix::<C, R>::default();
+        for i in 0..self.rows() {
+            for j in 0..self.cols() {
+                res[j][i] = self[i][j];
+            }
+        }
+        res
+    }
+
+    /// Element-wise XOR, i.e. addition over GF(2^8).
+    pub fn xor(&self, rhs: &Self) -> Self {
+        let mut res = Matrix::default();
+        for i in 0..self.rows() {
+            for j in 0..self.cols() {
+                res[i][j] = self[i][j] ^ rhs[i][j];
+            }
+        }
+        res
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use generic_array::{ArrayLength, GenericArray};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::{self, SeqAccess, Visitor};
+    use serde::ser::SerializeTuple;
+    use super::Matrix;
+
+    // A matrix is serialized as a tuple of its R * C cells in row-major
+    // order.
+    impl<R, C> Serialize for Matrix<R, C>
+        where R: ArrayLength<GenericArray<u8, C>>,
+              C: ArrayLength<u8>,
+    {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            let mut t = s.serialize_tuple(R::to_usize() * C::to_usize())?;
+            for row in self.state.iter() {
+                for cell in row.iter() {
+                    t.serialize_element(cell)?;
+                }
+            }
+            t.end()
+        }
+    }
+
+    struct MatrixVisitor<R, C>(PhantomData<(R, C)>);
+
+    impl<'de, R, C> Visitor<'de> for MatrixVisitor<R, C>
+        where R: ArrayLength<GenericArray<u8, C>>,
+              C: ArrayLength<u8>,
+    {
+        type Value = Matrix<R, C>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a {}x{} byte matrix", R::to_usize(), C::to_usize())
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where A: SeqAccess<'de>,
+        {
+            let mut m = Matrix::default();
+            let cols = C::to_usize();
+            for i in 0..R::to_usize() * cols {
+                m[i / cols][i % cols] = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+            Ok(m)
+        }
+    }
+
+    impl<'de, R, C> Deserialize<'de> for Matrix<R, C>
+        where R: ArrayLength<GenericArray<u8, C>>,
+              C: ArrayLength<u8>,
+    {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            d.deserialize_tuple(
+                R::to_usize() * C::to_usize(),
+                MatrixVisitor(PhantomData),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use consts::B;
+    use generic_array::typenum::{U8, U16};
+    use super::Matrix;
+
+    #[test]
+    fn test_mul_array_identity() {
+        let mut m = Matrix::<U8, U8>::default();
+        for i in 0..8 {
+            m[i][i] = 1;
+        }
+        let res = m.mul_array(&B);
+        for i in 0..8 {
+            assert_eq!(res[i][..], B[i][..]);
+        }
+    }
+
+    #[test]
+    fn test_mul_array_single_column() {
+        // Column 3 holds 0x80, so every product needs a reduction:
+        // 2 * 0x80 = 0x1b, 3 * 0x80 = 0x9b, 4 * 0x80 = 0x36,
+        // 5 * 0x80 = 0xb6 and 7 * 0x80 = 0xad.
+        let mut m = Matrix::<U8, U16>::default();
+        m[0][3] = 0x80;
+        let res = m.mul_array(&B);
+        let expected = [0x1b, 0xad, 0xb6, 0x9b, 0xb6, 0x36, 0x9b, 0x1b];
+        for i in 0..8 {
+            for j in 0..16 {
+                let e = if j == 3 { expected[i] } else { 0 };
+                assert_eq!(res[i][j], e, "row {} column {}", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_array_constant_column() {
+        // Each row of B XORs to 3, so a column of ones maps to threes.
+        let mut m = Matrix::<U8, U8>::default();
+        for i in 0..8 {
+            m[i][5] = 1;
+        }
+        let res = m.mul_array(&B);
+        for i in 0..8 {
+            assert_eq!(res[i][5], 3);
+        }
+    }
 }
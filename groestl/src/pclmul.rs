@@ -0,0 +1,96 @@
+//! `MixBytes` using carry-less multiplication (PCLMULQDQ), for the byte
+//! matrix backend.
+//!
+//! Every byte of a row is widened to a 16-bit lane, so that multiplying a
+//! 64-bit half of the row by a constant of at most three bits with
+//! `pclmulqdq` keeps each product inside its lane. As in `ssse3`, only the
+//! products by 2 and 4 are computed and the other coefficients are sums of
+//! those. The products are XORed together unreduced, and each output row is
+//! reduced once by folding the bits above the low byte back in with a second
+//! carry-less multiply by `0x1b`.
+
+use core::arch::x86_64::*;
+
+use generic_array::ArrayLength;
+use generic_array::typenum::U8;
+
+use consts::B;
+use matrix::Matrix;
+
+/// Whether the running CPU supports the instructions `mix_bytes` needs.
+pub fn available() -> bool {
+    is_x86_feature_detected!("pclmulqdq")
+}
+
+/// Multiplies both 64-bit halves of `x` by the polynomial in the low half of
+/// `c`, keeping the low 64 bits of each product.
+#[inline]
+#[target_feature(enable = "pclmulqdq")]
+unsafe fn clmul(x: __m128i, c: __m128i) -> __m128i {
+    let lo = _mm_clmulepi64_si128(x, c, 0x00);
+    let hi = _mm_clmulepi64_si128(x, c, 0x01);
+    _mm_unpacklo_epi64(lo, hi)
+}
+
+/// Reduces every 16-bit lane, holding a polynomial of degree at most 9,
+/// modulo `x^8 + x^4 + x^3 + x + 1`.
+#[inline]
+#[target_feature(enable = "pclmulqdq")]
+unsafe fn reduce(x: __m128i) -> __m128i {
+    let high = _mm_srli_epi16(x, 8);
+    let folded = _mm_xor_si128(x, clmul(high, _mm_cvtsi32_si128(0x1b)));
+    _mm_and_si128(folded, _mm_set1_epi16(0xff))
+}
+
+/// Overwrites `res` with B * `matrix`, for 8 or 16 columns.
+///
+/// # Safety
+///
+/// The caller must ensure that `available()` returned `true`.
+#[target_feature(enable = "pclmulqdq")]
+pub unsafe fn mix_bytes<C>(matrix: &Matrix<U8, C>, res: &mut Matrix<U8, C>)
+    where C: ArrayLength<u8>,
+{
+    let cols = matrix.cols();
+    debug_assert!(cols == 8 || cols == 16);
+    // Widened rows hold 8 columns per register.
+    let halves = cols / 8;
+    let zero = _mm_setzero_si128();
+    let two = _mm_cvtsi32_si128(2);
+    let four = _mm_cvtsi32_si128(4);
+
+    let mut x1 = [[zero; 2]; 8];
+    let mut x2 = [[zero; 2]; 8];
+    let mut x4 = [[zero; 2]; 8];
+    for k in 0..8 {
+        let mut bytes = [0u8; 16];
+        bytes[..cols].copy_from_slice(&matrix[k]);
+        let row = _mm_loadu_si128(bytes.as_ptr() as *const __m128i);
+        x1[k] = [_mm_unpacklo_epi8(row, zero), _mm_unpackhi_epi8(row, zero)];
+        for h in 0..halves {
+            x2[k][h] = clmul(x1[k][h], two);
+            x4[k][h] = clmul(x1[k][h], four);
+        }
+    }
+
+    for (i, coeffs) in B.iter().enumerate() {
+        let mut acc = [zero; 2];
+        for (k, &c) in coeffs.iter().enumerate() {
+            for h in 0..halves {
+                if c & 1 != 0 {
+                    acc[h] = _mm_xor_si128(acc[h], x1[k][h]);
+                }
+                if c & 2 != 0 {
+                    acc[h] = _mm_xor_si128(acc[h], x2[k][h]);
+                }
+                if c & 4 != 0 {
+                    acc[h] = _mm_xor_si128(acc[h], x4[k][h]);
+                }
+            }
+        }
+        let packed = _mm_packus_epi16(reduce(acc[0]), reduce(acc[1]));
+        let mut bytes = [0u8; 16];
+        _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, packed);
+        res[i].copy_from_slice(&bytes[..cols]);
+    }
+}
@@ -0,0 +1,142 @@
+//! P and Q permutations on a row-wise `u64` representation of the state.
+//!
+//! Each of the eight rows is held as one `u64` (two for the wide variant), with
+//! column 0 in the most significant byte of the first word. `ShiftBytes` then
+//! becomes a rotation of each row and `MixBytes` a handful of word-wide XORs
+//! and doublings in GF(2^8).
+
+use consts::B;
+use sbox;
+
+const LSB: u64 = 0x0101_0101_0101_0101;
+
+/// Permutes a 64 or 128 byte block in place. `q` selects the Q round
+/// constants and `original` the single-byte constants of Groestl-0.
+pub fn permute(
+    block: &mut [u8],
+    rounds: u8,
+    shifts: [u8; 8],
+    q: bool,
+    original: bool,
+) {
+    let words = block.len() / 64;
+    let mut rows = [[0u64; 2]; 8];
+    for (c, col) in block.chunks(8).enumerate() {
+        for (row, &b) in rows.iter_mut().zip(col.iter()) {
+            row[c / 8] |= (b as u64) << (56 - 8 * (c % 8));
+        }
+    }
+
+    for round in 0..rounds {
+        add_round_constant(&mut rows, words, round, q, original);
+        for row in rows.iter_mut() {
+            for w in row[..words].iter_mut() {
+                *w = sub_word(*w);
+            }
+        }
+        for (row, &shift) in rows.iter_mut().zip(shifts.iter()) {
+            rotate_row(row, words, shift as u32);
+        }
+        rows = mix_bytes(&rows, words);
+    }
+
+    for (c, col) in block.chunks_mut(8).enumerate() {
+        for (row, b) in rows.iter().zip(col.iter_mut()) {
+            *b = (row[c / 8] >> (56 - 8 * (c % 8))) as u8;
+        }
+    }
+}
+
+fn add_round_constant(
+    rows: &mut [[u64; 2]; 8],
+    words: usize,
+    round: u8,
+    q: bool,
+    original: bool,
+) {
+    if original {
+        if q {
+            rows[7][0] ^= ((0xff ^ round) as u64) << 56;
+        } else {
+            rows[0][0] ^= (round as u64) << 56;
+        }
+        return;
+    }
+    // Byte `c` of the constant row is `c << 4`, plus the round number.
+    const COLS: [u64; 2] = [0x0010_2030_4050_6070, 0x8090_a0b0_c0d0_e0f0];
+    let r = LSB * round as u64;
+    for w in 0..words {
+        if q {
+            for row in rows[..7].iter_mut() {
+                row[w] ^= !0;
+            }
+            rows[7][w] ^= !COLS[w] ^ r;
+        } else {
+            rows[0][w] ^= COLS[w] ^ r;
+        }
+    }
+}
+
+fn sub_word(w: u64) -> u64 {
+    let mut out = 0;
+    for i in 0..8 {
+        out |= (sbox::sub((w >> (8 * i)) as u8) as u64) << (8 * i);
+    }
+    out
+}
+
+/// Rotates a row left by `shift` bytes, so that column `j` receives the byte
+/// previously in column `j + shift`.
+fn rotate_row(row: &mut [u64; 2], words: usize, shift: u32) {
+    if words == 1 {
+        row[0] = row[0].rotate_left(8 * shift);
+        return;
+    }
+    let (mut hi, mut lo) = (row[0], row[1]);
+    let mut shift = shift;
+    if shift >= 8 {
+        ::core::mem::swap(&mut hi, &mut lo);
+        shift -= 8;
+    }
+    if shift != 0 {
+        let s = 8 * shift;
+        row[0] = (hi << s) | (lo >> (64 - s));
+        row[1] = (lo << s) | (hi >> (64 - s));
+    } else {
+        row[0] = hi;
+        row[1] = lo;
+    }
+}
+
+/// Multiplies every byte of `x` by 2 in GF(2^8).
+fn double(x: u64) -> u64 {
+    ((x & !(LSB << 7)) << 1) ^ (((x >> 7) & LSB) * 0x1b)
+}
+
+fn mix_bytes(rows: &[[u64; 2]; 8], words: usize) -> [[u64; 2]; 8] {
+    let mut out = [[0u64; 2]; 8];
+    for w in 0..words {
+        let mut x2 = [0u64; 8];
+        let mut x4 = [0u64; 8];
+        for k in 0..8 {
+            x2[k] = double(rows[k][w]);
+            x4[k] = double(x2[k]);
+        }
+        for (i, coeffs) in B.iter().enumerate() {
+            let mut acc = 0;
+            for (k, &c) in coeffs.iter().enumerate() {
+                if c & 1 != 0 {
+                    acc ^= rows[k][w];
+                }
+                if c & 2 != 0 {
+                    acc ^= x2[k];
+                }
+                if c & 4 != 0 {
+                    acc ^= x4[k];
+                }
+            }
+            out[i][w] = acc;
+        }
+    }
+    out
+}
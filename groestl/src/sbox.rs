@@ -0,0 +1,67 @@
+//! The Groestl (AES) S-box.
+//!
+//! By default this is a lookup into `SBOX`, which is itself generated at
+//! compile time from `ct_sub`. With the `ct-sbox` feature `ct_sub` is called
+//! directly: the GF(2^8) inverse followed by the AES affine map, with no
+//! data-dependent branches or memory accesses. The same feature has the
+//! scalar `MixBytes` multiply with `gf_mul` rather than its tables.
+
+#[cfg(not(feature = "ct-sbox"))]
+use consts::SBOX;
+
+#[cfg(not(feature = "ct-sbox"))]
+#[inline]
+pub fn sub(x: u8) -> u8 {
+    SBOX[x as usize]
+}
+
+#[cfg(feature = "ct-sbox")]
+#[inline]
+pub fn sub(x: u8) -> u8 {
+    ct_sub(x)
+}
+
+/// Multiplication in GF(2^8) modulo `x^8 + x^4 + x^3 + x + 1`, without
+/// data-dependent branches.
+pub const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut r = 0;
+    let mut i = 0;
+    while i < 8 {
+        r ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+        i += 1;
+    }
+    r
+}
+
+/// Constant-time S-box: `x^254` (the inverse, mapping 0 to 0) followed by the
+/// affine transformation.
+pub const fn ct_sub(x: u8) -> u8 {
+    let x2 = gf_mul(x, x);
+    let x3 = gf_mul(x2, x);
+    let x6 = gf_mul(x3, x3);
+    let x12 = gf_mul(x6, x6);
+    let x15 = gf_mul(x12, x3);
+    let x30 = gf_mul(x15, x15);
+    let x60 = gf_mul(x30, x30);
+    let x120 = gf_mul(x60, x60);
+    let x240 = gf_mul(x120, x120);
+    let x252 = gf_mul(x240, x12);
+    let inv = gf_mul(x252, x2);
+
+    inv ^ inv.rotate_left(1) ^ inv.rotate_left(2) ^ inv.rotate_left(3)
+        ^ inv.rotate_left(4) ^ 0x63
+}
+
+/// Builds the S-box lookup table at compile time.
+pub const fn table() -> [u8; 256] {
+    let mut t = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        t[i] = ct_sub(i as u8);
+        i += 1;
+    }
+    t
+}
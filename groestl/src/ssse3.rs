@@ -0,0 +1,184 @@
+//! P and Q permutations using SSSE3 only, for CPUs without AES-NI.
+//!
+//! Each row of the state lives in its own register with column `c` in lane
+//! `c`; the narrow variant leaves lanes 8 to 15 unused. `ShiftBytes` is a
+//! `pshufb` rotating every row, and `SubBytes` looks each byte up in the
+//! sixteen 16-byte slices of the S-box with `pshufb`, keeping the slice picked
+//! out by the high nibble. Every byte touches every slice, so the lookup does
+//! not depend on secret data.
+
+use core::arch::x86_64::*;
+
+use consts::SBOX;
+
+/// Whether the running CPU supports the instructions `permute` needs.
+pub fn available() -> bool {
+    is_x86_feature_detected!("ssse3")
+}
+
+/// Permutes a 64 or 128 byte block in place. `q` selects the Q round
+/// constants and `original` the single-byte constants of Groestl-0.
+///
+/// # Safety
+///
+/// The caller must ensure that `available()` returned `true`.
+#[target_feature(enable = "ssse3")]
+pub unsafe fn permute(
+    block: &mut [u8],
+    rounds: u8,
+    shifts: [u8; 8],
+    q: bool,
+    original: bool,
+) {
+    let cols = block.len() / 8;
+    let mut rows = load(block);
+
+    let mut masks = [_mm_setzero_si128(); 8];
+    for (mask, &shift) in masks.iter_mut().zip(shifts.iter()) {
+        let mut m = [0x80u8; 16];
+        for (j, b) in m[..cols].iter_mut().enumerate() {
+            *b = ((j + shift as usize) % cols) as u8;
+        }
+        *mask = _mm_loadu_si128(m.as_ptr() as *const __m128i);
+    }
+
+    let mut sbox = [_mm_setzero_si128(); 16];
+    for (slice, chunk) in sbox.iter_mut().zip(SBOX.chunks(16)) {
+        *slice = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+    }
+
+    for round in 0..rounds {
+        add_round_constant(&mut rows, round, q, original);
+        for (row, mask) in rows.iter_mut().zip(masks.iter()) {
+            *row = sub_bytes(_mm_shuffle_epi8(*row, *mask), &sbox);
+        }
+        rows = mix_bytes(&rows);
+    }
+
+    store(&rows, block);
+}
+
+/// Applies the S-box to every byte of `x`.
+#[inline]
+#[target_feature(enable = "ssse3")]
+unsafe fn sub_bytes(x: __m128i, sbox: &[__m128i; 16]) -> __m128i {
+    let nibble = _mm_set1_epi8(0x0f);
+    let lo = _mm_and_si128(x, nibble);
+    let hi = _mm_and_si128(_mm_srli_epi16(x, 4), nibble);
+    let mut out = _mm_setzero_si128();
+    for (h, slice) in sbox.iter().enumerate() {
+        let hit = _mm_cmpeq_epi8(hi, _mm_set1_epi8(h as i8));
+        let t = _mm_shuffle_epi8(*slice, lo);
+        out = _mm_or_si128(out, _mm_and_si128(hit, t));
+    }
+    out
+}
+
+/// Loads a 64 or 128 byte block as eight rows, column `c` in lane `c`.
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub unsafe fn load(block: &[u8]) -> [__m128i; 8] {
+    let mut bytes = [[0u8; 16]; 8];
+    for (c, col) in block.chunks(8).enumerate() {
+        for (row, &b) in bytes.iter_mut().zip(col.iter()) {
+            row[c] = b;
+        }
+    }
+    let mut rows = [_mm_setzero_si128(); 8];
+    for (row, b) in rows.iter_mut().zip(bytes.iter()) {
+        *row = _mm_loadu_si128(b.as_ptr() as *const __m128i);
+    }
+    rows
+}
+
+/// Stores the rows produced by `load` back into `block`.
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub unsafe fn store(rows: &[__m128i; 8], block: &mut [u8]) {
+    let mut bytes = [[0u8; 16]; 8];
+    for (b, row) in bytes.iter_mut().zip(rows.iter()) {
+        _mm_storeu_si128(b.as_mut_ptr() as *mut __m128i, *row);
+    }
+    for (c, col) in block.chunks_mut(8).enumerate() {
+        for (row, b) in bytes.iter().zip(col.iter_mut()) {
+            *b = row[c];
+        }
+    }
+}
+
+/// XORs the P or Q constants of `round` into the rows.
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub unsafe fn add_round_constant(
+    rows: &mut [__m128i; 8],
+    round: u8,
+    q: bool,
+    original: bool,
+) {
+    if original {
+        if q {
+            let rc = _mm_cvtsi32_si128((0xff ^ round) as i32);
+            rows[7] = _mm_xor_si128(rows[7], rc);
+        } else {
+            let rc = _mm_cvtsi32_si128(round as i32);
+            rows[0] = _mm_xor_si128(rows[0], rc);
+        }
+        return;
+    }
+
+    let mut consts = [0u8; 16];
+    for (c, b) in consts.iter_mut().enumerate() {
+        *b = ((c as u8) << 4) ^ round;
+    }
+    let rc = _mm_loadu_si128(consts.as_ptr() as *const __m128i);
+    if q {
+        let ones = _mm_set1_epi8(-1);
+        for row in rows[..7].iter_mut() {
+            *row = _mm_xor_si128(*row, ones);
+        }
+        rows[7] = _mm_xor_si128(rows[7], _mm_xor_si128(rc, ones));
+    } else {
+        rows[0] = _mm_xor_si128(rows[0], rc);
+    }
+}
+
+/// Multiplies every byte by 2 in GF(2^8).
+#[inline]
+#[target_feature(enable = "ssse3")]
+unsafe fn double(x: __m128i) -> __m128i {
+    let carry = _mm_cmplt_epi8(x, _mm_setzero_si128());
+    _mm_xor_si128(
+        _mm_add_epi8(x, x),
+        _mm_and_si128(carry, _mm_set1_epi8(0x1b)),
+    )
+}
+
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub unsafe fn mix_bytes(rows: &[__m128i; 8]) -> [__m128i; 8] {
+    use consts::B;
+
+    let mut x2 = [_mm_setzero_si128(); 8];
+    let mut x4 = [_mm_setzero_si128(); 8];
+    for k in 0..8 {
+        x2[k] = double(rows[k]);
+        x4[k] = double(x2[k]);
+    }
+    let mut out = [_mm_setzero_si128(); 8];
+    for (o, coeffs) in out.iter_mut().zip(B.iter()) {
+        let mut acc = _mm_setzero_si128();
+        for (k, &c) in coeffs.iter().enumerate() {
+            if c & 1 != 0 {
+                acc = _mm_xor_si128(acc, rows[k]);
+            }
+            if c & 2 != 0 {
+                acc = _mm_xor_si128(acc, x2[k]);
+            }
+            if c & 4 != 0 {
+                acc = _mm_xor_si128(acc, x4[k]);
+            }
+        }
+        *o = acc;
+    }
+    out
+}
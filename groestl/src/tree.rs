@@ -0,0 +1,145 @@
+//! The tree hash described in the crate documentation. The `0x00` and `0x01`
+//! prefixes keep a leaf digest from passing for a root.
+
+use generic_array::GenericArray;
+
+use groestl::{Groestl, ValidBlockSize, ValidOutputSize};
+
+const LEAF: u8 = 0x00;
+const ROOT: u8 = 0x01;
+
+fn hash_leaf<OutputSize, BlockSize>(
+    leaf: &[u8],
+) -> GenericArray<u8, OutputSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    let mut hasher = Groestl::<OutputSize, BlockSize>::new();
+    hasher.process(&[LEAF]);
+    hasher.process(leaf);
+    hasher.finalize()
+}
+
+fn leaf_count(len: usize, leaf_size: usize) -> usize {
+    assert!(leaf_size > 0, "leaf_size must not be zero");
+    if len == 0 {
+        1
+    } else {
+        (len - 1) / leaf_size + 1
+    }
+}
+
+/// Computes the tree hash of `data` on the calling thread.
+///
+/// # Panics
+///
+/// Panics if `leaf_size` is zero.
+pub fn hash_tree<OutputSize, BlockSize>(
+    data: &[u8],
+    leaf_size: usize,
+) -> GenericArray<u8, OutputSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    let mut root = Groestl::<OutputSize, BlockSize>::new();
+    root.process(&[ROOT]);
+    if leaf_count(data.len(), leaf_size) == 1 {
+        root.process(&hash_leaf::<OutputSize, BlockSize>(data));
+    } else {
+        for leaf in data.chunks(leaf_size) {
+            root.process(&hash_leaf::<OutputSize, BlockSize>(leaf));
+        }
+    }
+    root.finalize()
+}
+
+/// Computes the same digest as `hash_tree`, hashing the leaves on one
+/// thread per available core.
+///
+/// # Panics
+///
+/// Panics if `leaf_size` is zero.
+#[cfg(feature = "parallel")]
+pub fn hash_parallel<OutputSize, BlockSize>(
+    data: &[u8],
+    leaf_size: usize,
+) -> GenericArray<u8, OutputSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    let threads = ::std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    hash_on_threads::<OutputSize, BlockSize>(data, leaf_size, threads)
+}
+
+#[cfg(feature = "parallel")]
+fn hash_on_threads<OutputSize, BlockSize>(
+    data: &[u8],
+    leaf_size: usize,
+    threads: usize,
+) -> GenericArray<u8, OutputSize>
+    where OutputSize: ValidOutputSize<BlockSize>,
+          BlockSize: ValidBlockSize,
+          BlockSize::ArrayType: Copy,
+{
+    let count = leaf_count(data.len(), leaf_size);
+    let threads = threads.min(count);
+    if threads <= 1 {
+        return hash_tree::<OutputSize, BlockSize>(data, leaf_size);
+    }
+
+    // Each thread takes a run of consecutive leaves and writes their
+    // digests to its part of `digests`, so the root sees them in order.
+    let per_thread = (count - 1) / threads + 1;
+    let digest_bytes = OutputSize::to_usize();
+    let mut digests = ::alloc::vec![0; count * digest_bytes];
+    ::std::thread::scope(|s| {
+        let runs = digests
+            .chunks_mut(per_thread * digest_bytes)
+            .zip(data.chunks(per_thread * leaf_size));
+        for (out, run) in runs {
+            s.spawn(move || {
+                let leaves = run.chunks(leaf_size);
+                let outs = out.chunks_mut(digest_bytes);
+                for (digest, leaf) in outs.zip(leaves) {
+                    let d = hash_leaf::<OutputSize, BlockSize>(leaf);
+                    digest.copy_from_slice(&d);
+                }
+            });
+        }
+    });
+
+    let mut root = Groestl::<OutputSize, BlockSize>::new();
+    root.process(&[ROOT]);
+    root.process(&digests);
+    root.finalize()
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod test {
+    use generic_array::typenum::{U32, U64};
+
+    use super::{hash_on_threads, hash_tree};
+
+    #[test]
+    fn test_threads_match_tree() {
+        let mut data = [0u8; 700];
+        for (i, b) in data.iter_mut().enumerate() {
+            *b = (i * 13 % 256) as u8;
+        }
+        for &(len, leaf_size) in &[(0, 10), (1, 10), (700, 100), (650, 100)] {
+            let data = &data[..len];
+            let expected = hash_tree::<U32, U64>(data, leaf_size);
+            for &threads in &[2, 3, 4, 7, 100] {
+                assert_eq!(
+                    hash_on_threads::<U32, U64>(data, leaf_size, threads),
+                    expected,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Groestl with the digest size chosen at runtime.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use byte_tools::write_u64_be;
+use generic_array::GenericArray;
+use generic_array::typenum::{U32, U64, U128};
+
+use groestl::Groestl;
+use GroestlError;
+
+// The largest digest of the 128-byte block, and the longest digest overall.
+const MAX_OUTPUT_SIZE: usize = 64;
+
+#[derive(Clone, Debug)]
+enum Inner {
+    Small(Groestl<U32, U64>),
+    Big(Groestl<U64, U128>),
+}
+
+/// Groestl with the digest size chosen at runtime rather than as a `typenum`
+/// parameter.
+///
+/// Sizes up to 32 bytes use the 64-byte block and larger ones the 128-byte
+/// block, so the digest is the same as that of `GroestlSmall` or
+/// `GroestlBig` with the same size.
+#[derive(Clone, Debug)]
+pub struct GroestlVar {
+    inner: Inner,
+    output_size: usize,
+}
+
+impl GroestlVar {
+    /// Creates a hasher producing `output_size` bytes, which must be between
+    /// 1 and 64.
+    pub fn new(output_size: usize) -> Result<Self, GroestlError> {
+        if output_size == 0 || output_size > MAX_OUTPUT_SIZE {
+            return Err(GroestlError::InvalidOutputLen {
+                requested: output_size,
+                max: MAX_OUTPUT_SIZE,
+            });
+        }
+        let bits = 8 * output_size as u64;
+        let inner = if output_size <= 32 {
+            let mut iv = GenericArray::<u8, U64>::default();
+            write_u64_be(&mut iv[56..], bits);
+            Inner::Small(Groestl::with_iv(iv))
+        } else {
+            let mut iv = GenericArray::<u8, U128>::default();
+            write_u64_be(&mut iv[120..], bits);
+            Inner::Big(Groestl::with_iv(iv))
+        };
+        Ok(GroestlVar { inner, output_size })
+    }
+
+    /// Creates a hasher producing `output_size` bytes from the chaining
+    /// value `iv`. The length of `iv` picks the block size, 64 or 128 bytes,
+    /// and `output_size` can be at most half of it.
+    pub fn with_iv(
+        output_size: usize,
+        iv: &[u8],
+    ) -> Result<Self, GroestlError> {
+        let max = iv.len() / 2;
+        let inner = match iv.len() {
+            64 => Inner::Small(Groestl::with_iv(
+                GenericArray::clone_from_slice(iv),
+            )),
+            128 => Inner::Big(Groestl::with_iv(
+                GenericArray::clone_from_slice(iv),
+            )),
+            len => return Err(GroestlError::InvalidBlockSize(len)),
+        };
+        if output_size == 0 || output_size > max {
+            return Err(GroestlError::InvalidOutputLen {
+                requested: output_size,
+                max,
+            });
+        }
+        Ok(GroestlVar { inner, output_size })
+    }
+
+    /// Size of the digest in bytes.
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    /// Size of the internal block in bytes.
+    pub fn block_size(&self) -> usize {
+        match self.inner {
+            Inner::Small(ref g) => g.block_size(),
+            Inner::Big(ref g) => g.block_size(),
+        }
+    }
+
+    /// Number of bytes of input so far, including those still buffered.
+    pub fn bytes_hashed(&self) -> u64 {
+        match self.inner {
+            Inner::Small(ref g) => g.bytes_hashed(),
+            Inner::Big(ref g) => g.bytes_hashed(),
+        }
+    }
+
+    /// Digests `input`.
+    pub fn input(&mut self, input: &[u8]) {
+        match self.inner {
+            Inner::Small(ref mut g) => g.process(input),
+            Inner::Big(ref mut g) => g.process(input),
+        }
+    }
+
+    /// Finalizes the hash and copies it into `out`, which must be exactly
+    /// `output_size()` bytes long.
+    pub fn finalize_slice(self, out: &mut [u8]) -> Result<(), GroestlError> {
+        if out.len() != self.output_size {
+            return Err(GroestlError::WrongOutputLen {
+                expected: self.output_size,
+                actual: out.len(),
+            });
+        }
+        let res = match self.inner {
+            Inner::Small(g) => g.finalize_truncated(out),
+            Inner::Big(g) => g.finalize_truncated(out),
+        };
+        // The output size is checked against the block when constructing.
+        debug_assert!(res.is_ok());
+        Ok(())
+    }
+
+    /// Finalizes the hash and returns it.
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self) -> Vec<u8> {
+        let mut out = ::alloc::vec![0; self.output_size];
+        self.finalize_slice(&mut out).unwrap();
+        out
+    }
+}
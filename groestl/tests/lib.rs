@@ -4,12 +4,28 @@
 extern crate crypto_tests;
 extern crate generic_array;
 extern crate groestl;
+#[cfg(feature = "digest010")]
+extern crate hmac;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(feature = "async-io")]
+extern crate tokio;
 
 use crypto_tests::hash::{Test, main_test};
+use generic_array::GenericArray;
+use groestl::Digest;
 
 #[test]
 fn groestl_224_main() {
-    let tests = new_tests!("groestl224/test1");
+    let tests = new_tests!(
+        "groestl224/test1",
+        "groestl224/test2",
+        "groestl224/test3",
+        "groestl224/test4",
+        "groestl224/test5",
+    );
     main_test::<groestl::Groestl224>(&tests);
 }
 
@@ -19,18 +35,1703 @@ fn groestl_256_main() {
         "groestl256/test1",
         "groestl256/test2",
         "groestl256/test3",
+        "groestl256/test4",
+        "groestl256/test5",
     );
     main_test::<groestl::Groestl256>(&tests);
 }
 
 #[test]
 fn groestl_384_main() {
-    let tests = new_tests!("groestl384/test1");
+    let tests = new_tests!(
+        "groestl384/test1",
+        "groestl384/test2",
+        "groestl384/test3",
+        "groestl384/test4",
+        "groestl384/test5",
+    );
     main_test::<groestl::Groestl384>(&tests);
 }
 
 #[test]
 fn groestl_512_main() {
-    let tests = new_tests!("groestl512/test1");
+    let tests = new_tests!(
+        "groestl512/test1",
+        "groestl512/test2",
+        "groestl512/test3",
+        "groestl512/test4",
+        "groestl512/test5",
+        // 250 bytes: the padding spills into a third block.
+        "groestl512/test6",
+    );
     main_test::<groestl::Groestl512>(&tests);
 }
+
+// Groestl-0 vectors were produced with an independent implementation of the
+// round 1 specification, cross-checked against the final Groestl vectors.
+#[test]
+fn groestl0_224_main() {
+    let tests = new_tests!("groestl0_224/test1", "groestl0_224/test2");
+    main_test::<groestl::Groestl0_224>(&tests);
+}
+
+#[test]
+fn groestl0_256_main() {
+    let tests = new_tests!("groestl0_256/test1", "groestl0_256/test2");
+    main_test::<groestl::Groestl0_256>(&tests);
+}
+
+#[test]
+fn groestl0_384_main() {
+    let tests = new_tests!("groestl0_384/test1", "groestl0_384/test2");
+    main_test::<groestl::Groestl0_384>(&tests);
+}
+
+#[test]
+fn groestl0_512_main() {
+    let tests = new_tests!("groestl0_512/test1", "groestl0_512/test2");
+    main_test::<groestl::Groestl0_512>(&tests);
+}
+
+#[test]
+fn groestl_one_shot() {
+    assert_eq!(
+        groestl::groestl224(b"")[..],
+        include_bytes!("data/groestl224/test1.output.bin")[..],
+    );
+    assert_eq!(
+        groestl::groestl256(include_bytes!("data/groestl256/test2.input.bin"))[..],
+        include_bytes!("data/groestl256/test2.output.bin")[..],
+    );
+    assert_eq!(
+        groestl::groestl384(b"")[..],
+        include_bytes!("data/groestl384/test1.output.bin")[..],
+    );
+    assert_eq!(
+        groestl::groestl512(b"")[..],
+        include_bytes!("data/groestl512/test1.output.bin")[..],
+    );
+}
+
+#[test]
+fn groestl_reset() {
+    let mut hasher = groestl::Groestl256::new();
+    hasher.input(b"message A");
+    hasher.reset();
+    hasher.input(b"message B");
+
+    let mut fresh = groestl::Groestl256::new();
+    fresh.input(b"message B");
+    assert_eq!(hasher.result(), fresh.result());
+
+    let mut hasher = groestl::Groestl512::new();
+    hasher.input(&[0x5a; 200]);
+    hasher.reset();
+    assert_eq!(
+        hasher.result()[..],
+        include_bytes!("data/groestl512/test1.output.bin")[..],
+    );
+}
+
+#[test]
+fn groestl_reset_discards_iv() {
+    let mut iv = GenericArray::default();
+    iv[0] = 0x80;
+    let mut hasher = groestl::Groestl256::with_iv(iv);
+    hasher.input(b"abc");
+    hasher.reset();
+    assert_eq!(hasher, groestl::Groestl256::new());
+
+    let mut hasher = groestl::Groestl256::with_iv(iv);
+    hasher.input(b"abc");
+    assert_ne!(hasher.finalize_reset(), groestl::groestl256(b"abc"));
+    hasher.input(b"abc");
+    assert_eq!(hasher.finalize_reset(), groestl::groestl256(b"abc"));
+
+    let mut hasher = groestl::Groestl512::with_prefix(b"prefix");
+    hasher.reset();
+    assert_eq!(hasher, groestl::Groestl512::new());
+
+    let mut hasher = groestl::Groestl512::new();
+    hasher.rekey(GenericArray::default());
+    hasher.reset();
+    assert_eq!(hasher, groestl::Groestl512::new());
+}
+
+#[test]
+fn groestl_finalize_reset() {
+    let mut hasher = groestl::Groestl256::new();
+    hasher.input(include_bytes!("data/groestl256/test2.input.bin"));
+    assert_eq!(
+        hasher.finalize_reset()[..],
+        include_bytes!("data/groestl256/test2.output.bin")[..],
+    );
+    hasher.input(include_bytes!("data/groestl256/test3.input.bin"));
+    assert_eq!(
+        hasher.finalize_reset()[..],
+        include_bytes!("data/groestl256/test3.output.bin")[..],
+    );
+    assert_eq!(
+        hasher.result()[..],
+        include_bytes!("data/groestl256/test1.output.bin")[..],
+    );
+}
+
+#[test]
+fn groestl_process_bits() {
+    // Reference digests of the padded blocks c0 00 .. 01, ab 00 .. 01 and
+    // 61 c0 00 .. 01, i.e. the messages "1", "1010101" and "011000011".
+    let tests: [(&[u8], usize, [u8; 32]); 3] = [
+        (&[0xff], 1, [
+            0x23, 0xba, 0x9c, 0x1a, 0x96, 0x3c, 0x85, 0xb2,
+            0xfa, 0xb4, 0xcb, 0x86, 0xb0, 0x23, 0xf7, 0xe0,
+            0x9a, 0x81, 0xe9, 0x2d, 0xf0, 0x0d, 0xe7, 0x2c,
+            0x0b, 0xd0, 0x92, 0x17, 0xd3, 0xd5, 0xc6, 0x4c,
+        ]),
+        (&[0xab], 7, [
+            0x93, 0x53, 0x4b, 0xfd, 0xc0, 0x53, 0x02, 0xc0,
+            0x9e, 0xff, 0x48, 0xa5, 0x36, 0x61, 0xea, 0x74,
+            0x77, 0xe9, 0x29, 0x7b, 0x84, 0x71, 0xaa, 0xad,
+            0xca, 0x2c, 0x29, 0x3d, 0xa9, 0x1c, 0xc8, 0x12,
+        ]),
+        (&[0x61, 0xbf], 9, [
+            0xa0, 0xa5, 0x5e, 0x96, 0x7e, 0x3b, 0xcf, 0x3e,
+            0x4f, 0xd6, 0xdb, 0x1c, 0x24, 0x22, 0x14, 0xe1,
+            0x21, 0x90, 0x83, 0xce, 0x11, 0xd0, 0x45, 0xd1,
+            0x27, 0x16, 0x9b, 0xcc, 0x7b, 0x7f, 0x69, 0xb8,
+        ]),
+    ];
+    for &(input, bit_len, ref expected) in tests.iter() {
+        let mut hasher = groestl::Groestl256::new();
+        hasher.process_bits(input, bit_len).unwrap();
+        assert_eq!(hasher.result()[..], expected[..]);
+    }
+
+    let msg = include_bytes!("data/groestl256/test2.input.bin");
+    let mut hasher = groestl::Groestl256::new();
+    hasher.process_bits(msg, msg.len() * 8).unwrap();
+    assert_eq!(
+        hasher.result()[..],
+        include_bytes!("data/groestl256/test2.output.bin")[..],
+    );
+
+    let mut hasher = groestl::Groestl256::new();
+    assert_eq!(
+        hasher.process_bits(msg, msg.len() * 8 + 1),
+        Err(groestl::GroestlError::InvalidBitLength),
+    );
+    assert_eq!(
+        hasher.process_bits(&[0xff], 20),
+        Err(groestl::GroestlError::InvalidBitLength),
+    );
+    assert_eq!(hasher, groestl::Groestl256::new());
+
+    // Bits past `bit_len` in the last byte do not affect the digest.
+    let mut a = groestl::Groestl256::new();
+    a.process_bits(&[0xab], 7).unwrap();
+    let mut b = groestl::Groestl256::new();
+    b.process_bits(&[0xaa], 7).unwrap();
+    assert_eq!(a.result(), b.result());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn groestl_serde_resume() {
+    let msg = [0x42u8; 1000];
+
+    let mut hasher = groestl::Groestl512::new();
+    hasher.input(&msg[..437]);
+    let saved = serde_json::to_vec(&hasher).unwrap();
+    let mut resumed: groestl::Groestl512 =
+        serde_json::from_slice(&saved).unwrap();
+    resumed.input(&msg[437..]);
+
+    let mut expected = groestl::Groestl512::new();
+    expected.input(&msg);
+    assert_eq!(resumed.result(), expected.result());
+
+    let mut hasher = groestl::Groestl256::new();
+    hasher.process_bits(&msg[..3], 21).unwrap();
+    let saved = serde_json::to_vec(&hasher).unwrap();
+    let resumed: groestl::Groestl256 = serde_json::from_slice(&saved).unwrap();
+    assert_eq!(resumed.result(), hasher.result());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn groestl_serde_rejects_bad_state() {
+    use serde_json::Value;
+
+    let mut hasher = groestl::Groestl256::new();
+    hasher.process_bits(&[0xa0], 3).unwrap();
+    let saved = serde_json::to_value(hasher).unwrap();
+    let load = |field: usize, value: u8| {
+        let mut v = saved.clone();
+        v[field] = Value::from(value);
+        serde_json::from_value::<groestl::Groestl256>(v)
+    };
+    // The tuple is (buffer, chaining value, rounds, num_blocks, last_byte,
+    // last_bits).
+    assert!(load(4, 0xa0).is_ok());
+    assert!(load(4, 0xb0).is_err());
+    assert!(load(4, 0xa1).is_err());
+    assert!(load(5, 0).is_err());
+
+    let mut v = serde_json::to_value(groestl::Groestl256::new()).unwrap();
+    v[4] = Value::from(1);
+    assert!(serde_json::from_value::<groestl::Groestl256>(v).is_err());
+
+    let rounds = load(2, 5);
+    if cfg!(feature = "research") {
+        assert_eq!(rounds.unwrap().rounds(), 5);
+    } else {
+        assert!(rounds.is_err());
+    }
+    assert!(load(2, 10).is_ok());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn matrix_serde() {
+    use generic_array::typenum::{U8, U16};
+    use groestl::matrix::Matrix;
+
+    let mut m = Matrix::<U8, U16>::default();
+    for i in 0..8 {
+        for j in 0..16 {
+            m[i][j] = (i * 16 + j) as u8;
+        }
+    }
+    let saved = serde_json::to_vec(&m).unwrap();
+    assert!(saved.starts_with(b"[0,1,2,"));
+    let restored: Matrix<U8, U16> = serde_json::from_slice(&saved).unwrap();
+    assert_eq!(restored, m);
+
+    assert!(serde_json::from_slice::<Matrix<U8, U16>>(b"[0,1,2]").is_err());
+}
+
+#[test]
+fn groestl_sizes() {
+    let small = groestl::Groestl224::new();
+    assert_eq!(small.output_size(), 28);
+    assert_eq!(small.block_size(), 64);
+    assert_eq!(small.rounds(), 10);
+
+    let big = groestl::Groestl384::new();
+    assert_eq!(big.output_size(), 48);
+    assert_eq!(big.block_size(), 128);
+    assert_eq!(big.rounds(), 14);
+}
+
+#[test]
+fn groestl_const_size() {
+    let msg = b"The quick brown fox jumps over the lazy dog";
+    assert_eq!(
+        groestl::Groestl::<28>::digest(msg)[..],
+        groestl::Groestl224::digest(msg)[..],
+    );
+    assert_eq!(
+        groestl::Groestl::<32>::digest(msg)[..],
+        groestl::Groestl256::digest(msg)[..],
+    );
+    assert_eq!(
+        groestl::Groestl::<48>::digest(msg)[..],
+        groestl::Groestl384::digest(msg)[..],
+    );
+    assert_eq!(
+        groestl::Groestl::<64>::digest(msg)[..],
+        groestl::Groestl512::digest(msg)[..],
+    );
+
+    let mut hasher = groestl::Groestl::<33>::new();
+    hasher.input(&msg[..10]);
+    hasher.input(&msg[10..]);
+    assert_eq!(hasher.block_bytes(), 128);
+    let expected = groestl::GroestlBig::<generic_array::typenum::U33>::new()
+        .chain(msg)
+        .result();
+    assert_eq!(hasher.clone().result(), expected);
+    assert_eq!(hasher.finalize()[..], expected[..]);
+}
+
+#[test]
+fn groestl_block_boundary() {
+    // Messages of "a" filling whole blocks, so that the 0x80 byte and the
+    // length field need a padding block of their own.
+    let expected_256_64 = [
+        0x56, 0xe6, 0xd7, 0x68, 0x70, 0x91, 0x0b, 0x6d,
+        0x42, 0x58, 0xc6, 0xf5, 0xfd, 0xbe, 0xe8, 0x46,
+        0x87, 0x3f, 0x94, 0x43, 0x7d, 0x64, 0x09, 0xab,
+        0x53, 0x92, 0x2b, 0x91, 0xce, 0x4a, 0xfe, 0x8c,
+    ];
+    let expected_256_128 = [
+        0xad, 0xde, 0x33, 0x2d, 0xc4, 0x87, 0x35, 0xa7,
+        0xa9, 0x0e, 0x7c, 0xfc, 0x38, 0x43, 0x60, 0x9f,
+        0x21, 0xd2, 0xe1, 0xe2, 0x64, 0x24, 0x41, 0xd7,
+        0x80, 0xd9, 0x76, 0xe3, 0x8c, 0x5b, 0x8c, 0x91,
+    ];
+    let expected_512_128 = [
+        0x67, 0xaa, 0xf4, 0x83, 0x5a, 0x3b, 0xfa, 0xc2,
+        0x9d, 0xc0, 0x41, 0x31, 0x72, 0xaf, 0x0f, 0x73,
+        0xd5, 0xc4, 0x52, 0xc4, 0xab, 0x73, 0x18, 0x53,
+        0x5c, 0x6b, 0x4c, 0x4f, 0xa9, 0xfb, 0x87, 0xd4,
+        0xa5, 0xaa, 0xeb, 0xa1, 0xa3, 0x9f, 0xf2, 0x72,
+        0xb7, 0x95, 0xd6, 0xe3, 0xf7, 0x2e, 0xcd, 0x8d,
+        0x35, 0x37, 0xe2, 0xb9, 0x4a, 0x16, 0x82, 0xac,
+        0x7c, 0x48, 0x5e, 0xd3, 0x24, 0xde, 0x80, 0x36,
+    ];
+    let expected_512_256 = [
+        0x9a, 0xca, 0x6e, 0xe4, 0xaa, 0xe5, 0x46, 0xc7,
+        0xa4, 0xa3, 0x1d, 0x9f, 0x5c, 0xd3, 0x1e, 0xb4,
+        0x16, 0xd0, 0x29, 0xe3, 0x10, 0x9d, 0x32, 0x46,
+        0xb5, 0x18, 0xa7, 0x05, 0x14, 0x36, 0xc6, 0x5b,
+        0x80, 0x20, 0x60, 0x0e, 0xd2, 0xf1, 0x42, 0x03,
+        0x6b, 0xb6, 0x20, 0x6c, 0x66, 0xd6, 0xc5, 0x00,
+        0xcd, 0x07, 0xef, 0x50, 0x07, 0x6d, 0x6b, 0x33,
+        0xc1, 0xab, 0x3e, 0x31, 0x9d, 0xb1, 0x8d, 0xee,
+    ];
+    let msg = [0x61; 256];
+
+    let mut sh = groestl::Groestl256::new();
+    sh.input(&msg[..64]);
+    assert_eq!(sh.blocks_processed(), 1);
+    assert_eq!(sh.result()[..], expected_256_64[..]);
+    let mut sh = groestl::Groestl256::new();
+    sh.input(&msg[..128]);
+    assert_eq!(sh.blocks_processed(), 2);
+    assert_eq!(sh.result()[..], expected_256_128[..]);
+    assert_eq!(groestl::groestl256(&msg[..64])[..], expected_256_64[..]);
+
+    let mut sh = groestl::Groestl512::new();
+    sh.input(&msg[..128]);
+    assert_eq!(sh.blocks_processed(), 1);
+    assert_eq!(sh.result()[..], expected_512_128[..]);
+    let mut sh = groestl::Groestl512::new();
+    sh.input(&msg);
+    assert_eq!(sh.blocks_processed(), 2);
+    assert_eq!(sh.result()[..], expected_512_256[..]);
+    assert_eq!(groestl::groestl512(&msg[..128])[..], expected_512_128[..]);
+}
+
+#[test]
+fn groestl_bytes_hashed() {
+    let chunks: [&[u8]; 5] = [b"abc", &[0; 61], &[1; 200], b"", &[2; 7]];
+
+    let mut small = groestl::Groestl256::new();
+    let mut big = groestl::Groestl512::new();
+    let mut var = groestl::GroestlVar::new(40).unwrap();
+    let mut total = 0;
+    for chunk in chunks.iter() {
+        small.input(chunk);
+        big.input(chunk);
+        var.input(chunk);
+        total += chunk.len() as u64;
+        assert_eq!(small.bytes_hashed(), total);
+        assert_eq!(big.bytes_hashed(), total);
+        assert_eq!(var.bytes_hashed(), total);
+    }
+    assert_eq!(total, 271);
+    assert_eq!(groestl::Groestl256::with_prefix(b"tag").bytes_hashed(), 3);
+}
+
+#[test]
+fn groestl_blocks_processed() {
+    let mut hasher = groestl::Groestl256::new();
+    assert_eq!(hasher.blocks_processed(), 0);
+    hasher.input(&[0x61; 160]);
+    assert_eq!(hasher.blocks_processed(), 2);
+
+    let mut hasher = groestl::Groestl512::new();
+    hasher.input(&[0x61; 320]);
+    assert_eq!(hasher.blocks_processed(), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn groestl_io_write() {
+    use std::io;
+
+    let input = include_bytes!("data/groestl256/test2.input.bin");
+    let mut hasher = groestl::Groestl512::new();
+    let copied = io::copy(&mut io::Cursor::new(&input[..]), &mut hasher)
+        .unwrap();
+    assert_eq!(copied, input.len() as u64);
+
+    let mut expected = groestl::Groestl512::new();
+    expected.input(input);
+    assert_eq!(hasher.result(), expected.result());
+}
+
+#[cfg(feature = "async-io")]
+#[test]
+fn groestl_async_write() {
+    let input = include_bytes!("data/groestl256/test2.input.bin");
+    let mut hasher = groestl::Groestl512::new();
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let copied = rt
+        .block_on(tokio::io::copy(&mut &input[..], &mut hasher))
+        .unwrap();
+    assert_eq!(copied, input.len() as u64);
+
+    let mut expected = groestl::Groestl512::new();
+    expected.input(input);
+    assert_eq!(hasher.result(), expected.result());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn groestl_hash_file() {
+    use std::io::Write;
+
+    let input = include_bytes!("data/groestl512/test2.input.bin");
+    let path = std::env::temp_dir()
+        .join(std::format!("groestl-hash-file-{}", std::process::id()));
+    std::fs::File::create(&path).unwrap().write_all(input).unwrap();
+    let digest = groestl::Groestl512::hash_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        digest.unwrap()[..],
+        include_bytes!("data/groestl512/test2.output.bin")[..],
+    );
+
+    let err = groestl::Groestl256::hash_file(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    // Spans several reads of the 64 KiB file buffer, ending mid-buffer.
+    let input: std::vec::Vec<u8> =
+        (0..200_001u32).map(|i| (i % 251) as u8).collect();
+    std::fs::File::create(&path).unwrap().write_all(&input).unwrap();
+    let digest = groestl::Groestl256::hash_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(digest.unwrap(), groestl::groestl256(&input));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn groestl_debug() {
+    let mut hasher = groestl::Groestl512::new();
+    hasher.input(&[0x61; 130]);
+    assert_eq!(
+        std::format!("{:?}", hasher),
+        "GroestlBig { groestl: Groestl { output_size: 64, block_size: 128, \
+         blocks_processed: 1, buffered: 2 } }",
+    );
+}
+
+#[test]
+fn groestl_chain() {
+    let mut expected = groestl::Groestl256::new();
+    expected.input(b"The quick brown fox ");
+    expected.input(b"jumps over the lazy dog");
+
+    let chained = groestl::Groestl256::new()
+        .chain(b"The quick brown fox ")
+        .chain("jumps over the lazy dog");
+    assert_eq!(chained.result(), expected.result());
+}
+
+#[test]
+fn groestl_update() {
+    let mut hasher = groestl::Groestl512::new();
+    hasher.update(b"The quick brown fox ").update("jumps over the lazy dog");
+
+    let mut expected = groestl::Groestl512::new();
+    expected.input(b"The quick brown fox jumps over the lazy dog");
+    assert_eq!(hasher.result(), expected.result());
+}
+
+#[test]
+fn groestl_verify() {
+    let expected = include_bytes!("data/groestl256/test1.output.bin");
+    assert!(groestl::Groestl256::new().verify(expected));
+
+    let mut wrong = *expected;
+    wrong[31] ^= 1;
+    assert!(!groestl::Groestl256::new().verify(&wrong));
+    assert!(!groestl::Groestl256::new().verify(&expected[..31]));
+}
+
+#[test]
+fn groestl_verify_hex() {
+    let hex =
+        "1a52d11d550039be16107f9c58db9ebcc417f16f736adb2502567119f0083467";
+    assert_eq!(groestl::Groestl256::new().verify_hex(hex), Ok(true));
+    let upper =
+        "1A52D11D550039BE16107F9C58DB9EBCC417F16F736ADB2502567119F0083467";
+    assert_eq!(groestl::Groestl256::new().verify_hex(upper), Ok(true));
+
+    let wrong =
+        "1a52d11d550039be16107f9c58db9ebcc417f16f736adb2502567119f0083466";
+    assert_eq!(groestl::Groestl256::new().verify_hex(wrong), Ok(false));
+
+    assert_eq!(
+        groestl::Groestl256::new().verify_hex(&hex[..62]),
+        Err(groestl::GroestlError::WrongOutputLen {
+            expected: 32,
+            actual: 31,
+        }),
+    );
+    assert_eq!(
+        groestl::Groestl256::new().verify_hex(&hex[..63]),
+        Err(groestl::GroestlError::InvalidHex),
+    );
+    let bad =
+        "1a52d11d550039be16107f9c58db9ebcc417f16f736adb2502567119f00834g7";
+    assert_eq!(
+        groestl::Groestl256::new().verify_hex(bad),
+        Err(groestl::GroestlError::InvalidHex),
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn groestl_finalize_hex() {
+    assert_eq!(
+        groestl::Groestl256::new().finalize_hex(),
+        "1a52d11d550039be16107f9c58db9ebcc417f16f736adb2502567119f0083467",
+    );
+    assert_eq!(
+        groestl::Groestl256::new().finalize_hex_upper(),
+        "1A52D11D550039BE16107F9C58DB9EBCC417F16F736ADB2502567119F0083467",
+    );
+}
+
+#[test]
+fn groestl_hasher() {
+    use core::hash::Hasher;
+
+    let mut hasher = groestl::GroestlHasher::default();
+    hasher.write(b"");
+    // First 8 bytes of the Groestl-256 empty-string digest.
+    assert_eq!(hasher.finish(), 0x1a52_d11d_5500_39be);
+    assert_eq!(hasher.finish(), 0x1a52_d11d_5500_39be);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn groestl_hash_map() {
+    use std::collections::HashMap;
+
+    let mut map = HashMap::with_hasher(groestl::BuildGroestlHasher);
+    map.insert("fox", 1);
+    map.insert("dog", 2);
+    assert_eq!(map.get("fox"), Some(&1));
+    assert_eq!(map.get("dog"), Some(&2));
+}
+
+#[test]
+fn groestl_with_iv() {
+    let input = include_bytes!("data/groestl256/test2.input.bin");
+
+    // The standard IV is the output size in bits, big-endian, at the end of
+    // an otherwise zero block.
+    let mut iv = GenericArray::default();
+    iv[62] = 1;
+    let mut hasher = groestl::Groestl256::with_iv(iv);
+    hasher.input(input);
+    assert_eq!(
+        hasher.result()[..],
+        include_bytes!("data/groestl256/test2.output.bin")[..],
+    );
+
+    let mut iv = GenericArray::default();
+    iv[126] = 2;
+    let hasher = groestl::Groestl512::with_iv(iv);
+    assert_eq!(
+        hasher.result()[..],
+        include_bytes!("data/groestl512/test1.output.bin")[..],
+    );
+}
+
+// HMAC (RFC 2104) written against `Digest` alone, which is all that an HMAC
+// implementation needs from the hash as long as `block_bytes` reports the
+// message block size.
+fn hmac<D: Digest + Default>(
+    key: &[u8],
+    msg: &[u8],
+) -> GenericArray<u8, D::OutputSize> {
+    let block = D::default().block_bytes();
+    let mut k = [0u8; 128];
+    if key.len() > block {
+        let mut d = D::default();
+        d.input(key);
+        let h = d.result();
+        k[..h.len()].copy_from_slice(&h);
+    } else {
+        k[..key.len()].copy_from_slice(key);
+    }
+
+    let mut pad = [0u8; 128];
+    for (p, k) in pad.iter_mut().zip(k.iter()) {
+        *p = k ^ 0x36;
+    }
+    let mut inner = D::default();
+    inner.input(&pad[..block]);
+    inner.input(msg);
+    let inner = inner.result();
+
+    for (p, k) in pad.iter_mut().zip(k.iter()) {
+        *p = k ^ 0x5c;
+    }
+    let mut outer = D::default();
+    outer.input(&pad[..block]);
+    outer.input(&inner);
+    outer.result()
+}
+
+#[test]
+fn groestl_hmac() {
+    assert_eq!(groestl::Groestl256::default().block_bytes(), 64);
+    assert_eq!(groestl::Groestl512::default().block_bytes(), 128);
+
+    let msg = b"The quick brown fox jumps over the lazy dog";
+    let expected = [
+        0x60, 0xb2, 0x2c, 0xd3, 0x38, 0x99, 0x14, 0x90,
+        0x80, 0x27, 0x9e, 0xac, 0x45, 0x10, 0xef, 0xfc,
+        0xe2, 0x0d, 0xb3, 0x35, 0xb6, 0xdb, 0x8f, 0xa1,
+        0xad, 0x3a, 0x26, 0x18, 0x63, 0x09, 0x8f, 0x84,
+    ];
+    assert_eq!(hmac::<groestl::Groestl256>(b"key", msg)[..], expected[..]);
+
+    // A key longer than the block is hashed first.
+    let expected = [
+        0x28, 0x80, 0x3b, 0xf7, 0x7e, 0x9d, 0xa7, 0x37,
+        0x5e, 0x9f, 0x9c, 0x2f, 0xcb, 0xe9, 0xf2, 0xb4,
+        0x96, 0xcb, 0x31, 0x69, 0x31, 0x39, 0x0a, 0x2d,
+        0xf3, 0x28, 0x17, 0xa5, 0xb3, 0xd8, 0x86, 0x45,
+    ];
+    assert_eq!(
+        hmac::<groestl::Groestl256>(&[b'a'; 100], msg)[..],
+        expected[..],
+    );
+}
+
+#[cfg(feature = "digest010")]
+#[test]
+fn groestl_digest010() {
+    use hmac::{Hmac, Mac, SimpleHmac};
+    use hmac::digest::Digest;
+
+    let msg = b"The quick brown fox jumps over the lazy dog";
+    let expected = [
+        0x60, 0xb2, 0x2c, 0xd3, 0x38, 0x99, 0x14, 0x90,
+        0x80, 0x27, 0x9e, 0xac, 0x45, 0x10, 0xef, 0xfc,
+        0xe2, 0x0d, 0xb3, 0x35, 0xb6, 0xdb, 0x8f, 0xa1,
+        0xad, 0x3a, 0x26, 0x18, 0x63, 0x09, 0x8f, 0x84,
+    ];
+    let mut mac =
+        Hmac::<groestl::core_api::Groestl256>::new_from_slice(b"key")
+            .unwrap();
+    mac.update(msg);
+    assert_eq!(mac.finalize().into_bytes()[..], expected[..]);
+    let mut mac =
+        SimpleHmac::<groestl::Groestl256>::new_from_slice(b"key").unwrap();
+    mac.update(msg);
+    assert_eq!(mac.finalize().into_bytes()[..], expected[..]);
+
+    // Every length up to past the ends of both block sizes.
+    let data = [0x61; 140];
+    for len in 0..data.len() {
+        let data = &data[..len];
+        let expected = groestl::groestl256(data);
+        assert_eq!(
+            groestl::core_api::Groestl256::digest(data)[..],
+            expected[..],
+        );
+        assert_eq!(
+            <groestl::Groestl256 as Digest>::digest(data)[..],
+            expected[..],
+        );
+        assert_eq!(
+            groestl::core_api::Groestl512::digest(data)[..],
+            groestl::groestl512(data)[..],
+        );
+    }
+}
+
+#[cfg(all(feature = "digest010", feature = "std"))]
+#[test]
+fn groestl_dyn_digest() {
+    use hmac::digest::DynDigest;
+    use std::boxed::Box;
+
+    fn check(mut hasher: Box<dyn DynDigest>, expected: &[u8]) {
+        assert_eq!(hasher.output_size(), expected.len());
+        hasher.update(b"ab");
+        let mut copy = hasher.box_clone();
+        hasher.update(b"c");
+        copy.update(b"c");
+        assert_eq!(&hasher.finalize_reset()[..], expected);
+        assert_eq!(&copy.finalize()[..], expected);
+
+        // The reset hasher starts over.
+        hasher.update(b"abc");
+        assert_eq!(&hasher.finalize_reset()[..], expected);
+    }
+
+    check(Box::new(groestl::Groestl256::new()), &groestl::groestl256(b"abc"));
+    check(Box::new(groestl::Groestl512::new()), &groestl::groestl512(b"abc"));
+
+    // Resetting goes back to the standard IV, not to a custom one.
+    let mut hasher: Box<dyn DynDigest> =
+        Box::new(groestl::Groestl256::with_iv(GenericArray::default()));
+    hasher.update(b"abc");
+    assert_ne!(&hasher.finalize_reset()[..], &groestl::groestl256(b"abc")[..]);
+    hasher.update(b"abc");
+    assert_eq!(&hasher.finalize_reset()[..], &groestl::groestl256(b"abc")[..]);
+}
+
+#[cfg(feature = "digest010")]
+#[test]
+fn groestl_digest010_output() {
+    use hmac::digest::{Digest, FixedOutput, FixedOutputReset, Output};
+
+    let mut hasher = groestl::Groestl256::new();
+    Digest::update(&mut hasher, b"abc");
+    let out: Output<groestl::Groestl256> = hasher.finalize();
+    assert_eq!(out.len(), 32);
+    assert_eq!(out[..], groestl::groestl256(b"abc")[..]);
+
+    let mut out = Output::<groestl::Groestl512>::default();
+    let mut hasher = groestl::Groestl512::new();
+    Digest::update(&mut hasher, b"abc");
+    FixedOutputReset::finalize_into_reset(&mut hasher, &mut out);
+    assert_eq!(out[..], groestl::groestl512(b"abc")[..]);
+    Digest::update(&mut hasher, b"abc");
+    FixedOutput::finalize_into(hasher, &mut out);
+    assert_eq!(out[..], groestl::groestl512(b"abc")[..]);
+
+    let out: Output<groestl::core_api::Groestl224> =
+        groestl::core_api::Groestl224::digest(b"abc");
+    assert_eq!(out[..], groestl::groestl224(b"abc")[..]);
+}
+
+#[test]
+fn groestl_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<groestl::Groestl224>();
+    assert_send_sync::<groestl::Groestl256>();
+    assert_send_sync::<groestl::Groestl384>();
+    assert_send_sync::<groestl::Groestl512>();
+    assert_send_sync::<groestl::GroestlSmall<generic_array::typenum::U8>>();
+    assert_send_sync::<groestl::GroestlBig<generic_array::typenum::U52>>();
+    assert_send_sync::<groestl::Groestl0_256>();
+    assert_send_sync::<groestl::Groestl0_512>();
+    assert_send_sync::<groestl::GroestlVar>();
+    assert_send_sync::<groestl::GroestlHasher>();
+    assert_send_sync::<groestl::BuildGroestlHasher>();
+    assert_send_sync::<groestl::GroestlError>();
+    #[cfg(feature = "digest010")]
+    {
+        assert_send_sync::<groestl::core_api::Groestl256>();
+        assert_send_sync::<groestl::core_api::Groestl512>();
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn groestl_padded_blocks() {
+    use generic_array::typenum::U128;
+
+    // Builds the padded message as the specification describes it.
+    fn pad(msg: &[u8], block: usize, out: &mut [u8; 384]) -> usize {
+        out[..msg.len()].copy_from_slice(msg);
+        out[msg.len()] = 0x80;
+        let mut len = msg.len() + 1;
+        while len % block != block - 8 {
+            out[len] = 0;
+            len += 1;
+        }
+        let blocks = (len / block + 1) as u64;
+        for i in 0..8 {
+            out[len + i] = (blocks >> (56 - 8 * i)) as u8;
+        }
+        len + 8
+    }
+
+    let data = [0x61; 250];
+    let mut padded = [0u8; 384];
+    for &len in &[0, 3, 55, 56, 63, 64, 100, 130] {
+        let n = pad(&data[..len], 64, &mut padded);
+        let tail = &padded[len / 64 * 64..n];
+        let blocks = groestl::Groestl256::new()
+            .chain(&data[..len])
+            .padded_blocks();
+        assert_eq!(blocks.len(), tail.len() / 64);
+        for (block, expected) in blocks.iter().zip(tail.chunks(64)) {
+            assert_eq!(block[..], expected[..]);
+        }
+    }
+
+    for &len in &[0, 119, 120, 250] {
+        let n = pad(&data[..len], 128, &mut padded);
+        let tail = &padded[len / 128 * 128..n];
+        let blocks = groestl::Groestl512::new()
+            .chain(&data[..len])
+            .padded_blocks();
+        assert_eq!(blocks.len(), tail.len() / 128);
+        for (block, expected) in blocks.iter().zip(tail.chunks(128)) {
+            assert_eq!(block[..], expected[..]);
+        }
+
+        // With no full block before them, compressing the blocks from the
+        // IV and applying the output transformation gives the digest.
+        if len < 128 {
+            let mut iv = GenericArray::<u8, U128>::default();
+            iv[126] = 0x02;
+            let h = groestl::compress_pure(&iv, &blocks);
+            assert_eq!(
+                groestl::output_transform(&h),
+                groestl::groestl512(&data[..len]),
+            );
+        }
+    }
+}
+
+#[test]
+fn groestl_self_test() {
+    assert_eq!(groestl::self_test(), Ok(()));
+}
+
+#[test]
+fn groestl_chunking_patterns() {
+    // Feeds the message in every pattern of three chunk sizes, repeated
+    // until it runs out, from sizes that straddle the block boundaries.
+    macro_rules! check {
+        ($hasher:ty, $block:expr) => {
+            let mut data = [0u8; 3 * $block + 5];
+            for (i, b) in data.iter_mut().enumerate() {
+                *b = (i * 29 + 3) as u8;
+            }
+            let expected = <$hasher>::digest(&data);
+            let sizes = [1, $block - 1, $block, $block + 1, 2 * $block];
+            for &a in &sizes {
+                for &b in &sizes {
+                    for &c in &sizes {
+                        let mut sh = <$hasher>::new();
+                        let mut rest = &data[..];
+                        for &n in [a, b, c].iter().cycle() {
+                            if rest.is_empty() {
+                                break;
+                            }
+                            let n = n.min(rest.len());
+                            sh.input(&rest[..n]);
+                            rest = &rest[n..];
+                        }
+                        assert_eq!(sh.result(), expected);
+                    }
+                }
+            }
+        };
+    }
+    check!(groestl::Groestl256, 64);
+    check!(groestl::Groestl512, 128);
+}
+
+#[test]
+fn groestl_bulk_matches_bytewise() {
+    let mut data = [0u8; 10 * 1024];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i * 7 + i / 256) as u8;
+    }
+
+    let mut bulk = groestl::Groestl256::new();
+    bulk.input(&data);
+    let mut bytewise = groestl::Groestl256::new();
+    for b in data.chunks(1) {
+        bytewise.input(b);
+    }
+    assert_eq!(bulk.result(), bytewise.result());
+
+    let mut bulk = groestl::Groestl512::new();
+    bulk.input(&data[3..]);
+    let mut bytewise = groestl::Groestl512::new();
+    for b in data[3..].chunks(1) {
+        bytewise.input(b);
+    }
+    assert_eq!(bulk.result(), bytewise.result());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn groestl_digest_reader() {
+    use std::io;
+
+    // Fails every other read with Interrupted, which must be retried.
+    struct Flaky<R> {
+        inner: R,
+        fail: bool,
+    }
+
+    impl<R: io::Read> io::Read for Flaky<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.fail = !self.fail;
+            if self.fail {
+                Err(io::Error::new(io::ErrorKind::Interrupted, "again"))
+            } else {
+                self.inner.read(buf)
+            }
+        }
+    }
+
+    let mut data = [0u8; 5000];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i % 251) as u8;
+    }
+    let mut expected = groestl::Groestl256::new();
+    expected.input(&data);
+    let expected = expected.result();
+
+    let reader = io::Cursor::new(&data[..]);
+    let result = groestl::Groestl256::new().digest_reader(reader).unwrap();
+    assert_eq!(result, expected);
+
+    let reader = Flaky { inner: io::Cursor::new(&data[..]), fail: false };
+    let result = groestl::Groestl256::new().digest_reader(reader).unwrap();
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn groestl_finalize_truncated() {
+    // The last 32 of these are the Groestl-256 digest of the empty message.
+    let expected = [
+        0x6d, 0x65, 0x3c, 0x7b, 0x43, 0xe1, 0x3a, 0x9f,
+        0x1a, 0x52, 0xd1, 0x1d, 0x55, 0x00, 0x39, 0xbe,
+        0x16, 0x10, 0x7f, 0x9c, 0x58, 0xdb, 0x9e, 0xbc,
+        0xc4, 0x17, 0xf1, 0x6f, 0x73, 0x6a, 0xdb, 0x25,
+        0x02, 0x56, 0x71, 0x19, 0xf0, 0x08, 0x34, 0x67,
+    ];
+    let mut out = [0u8; 40];
+    groestl::Groestl256::new().finalize_truncated(&mut out).unwrap();
+    assert_eq!(out[..], expected[..]);
+
+    let expected = [
+        0xa7, 0x7b, 0xae, 0xde, 0x15, 0x57, 0xf5, 0xd0,
+        0x99, 0xfc, 0xe0, 0xc0, 0x3d, 0x6d, 0xc2, 0xba,
+        0x8e, 0x6d, 0x4a, 0x66, 0x33, 0xdf, 0xbd, 0x66,
+        0x05, 0x3c, 0x20, 0xfa, 0xa8, 0x7d, 0x1a, 0x11,
+        0xf3, 0x9a, 0x7f, 0xbe, 0x4a, 0x6c, 0x2f, 0x00,
+        0x98, 0x01, 0x37, 0x03, 0x08, 0xfc, 0x4a, 0xd8,
+    ];
+    let mut out = [0u8; 48];
+    groestl::Groestl512::new().finalize_truncated(&mut out).unwrap();
+    assert_eq!(out[..], expected[..]);
+
+    let mut out = [0u8; 65];
+    assert_eq!(
+        groestl::Groestl256::new().finalize_truncated(&mut out),
+        Err(groestl::GroestlError::OutputTooLong),
+    );
+}
+
+#[test]
+fn groestl_extend() {
+    let mut data = [0u8; 1000];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i * 31 + 5) as u8;
+    }
+
+    let mut whole = groestl::Groestl256::new();
+    whole.input(&data);
+    let whole = whole.result();
+
+    let mut chunks = groestl::Groestl256::new();
+    chunks.extend(data.chunks(37));
+    assert_eq!(chunks.result(), whole);
+
+    let mut bytes = groestl::Groestl256::new();
+    bytes.extend(data.iter().cloned());
+    assert_eq!(bytes.result(), whole);
+
+    let mut mixed = groestl::Groestl512::new();
+    mixed.extend(data[..300].iter().cloned());
+    mixed.extend(data[300..].chunks(129));
+    let mut whole = groestl::Groestl512::new();
+    whole.input(&data);
+    assert_eq!(mixed.result(), whole.result());
+}
+
+#[test]
+fn groestl_clone_finalize() {
+    let data = b"The quick brown fox jumps over the lazy dog";
+
+    let mut sh = groestl::Groestl512::new();
+    let mut checkpoints = [GenericArray::default(); 4];
+    for (i, chunk) in data.chunks(11).enumerate() {
+        sh.input(chunk);
+        checkpoints[i] = sh.clone_finalize();
+    }
+    assert_eq!(checkpoints[3], sh.result());
+
+    let prefix = groestl::Groestl512::new().chain(&data[..22]).result();
+    assert_eq!(checkpoints[1], prefix);
+    assert_eq!(
+        checkpoints[3],
+        groestl::groestl512(data),
+    );
+}
+
+#[test]
+fn groestl_digest() {
+    macro_rules! check {
+        ($hasher:ty, $dir:expr) => {
+            let empty =
+                include_bytes!(concat!("data/", $dir, "/test1.output.bin"));
+            assert_eq!(<$hasher>::digest(b"")[..], empty[..]);
+            let abc =
+                include_bytes!(concat!("data/", $dir, "/test4.output.bin"));
+            assert_eq!(<$hasher>::digest(b"abc")[..], abc[..]);
+        };
+    }
+    check!(groestl::Groestl224, "groestl224");
+    check!(groestl::Groestl256, "groestl256");
+    check!(groestl::Groestl384, "groestl384");
+    check!(groestl::Groestl512, "groestl512");
+
+    let out: GenericArray<u8, generic_array::typenum::U48> =
+        groestl::Groestl384::digest(b"abc");
+    assert_eq!(out.len(), 48);
+}
+
+#[test]
+fn groestl_digest_iter() {
+    let data = [0x5a; 300];
+    let chunks: [&[u8]; 5] = [
+        &data[..1], &data[1..64], &data[64..64], &data[64..250], &data[250..],
+    ];
+    assert_eq!(
+        groestl::Groestl256::digest_iter(chunks),
+        groestl::groestl256(&data),
+    );
+    assert_eq!(
+        groestl::Groestl512::digest_iter(chunks.iter()),
+        groestl::groestl512(&data),
+    );
+    assert_eq!(
+        groestl::Groestl224::digest_iter(data.chunks(7)),
+        groestl::groestl224(&data),
+    );
+    let none: [[u8; 0]; 0] = [];
+    assert_eq!(
+        groestl::Groestl384::digest_iter(none),
+        groestl::groestl384(b""),
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn groestl_digest_iter_vec() {
+    let chunks: std::vec::Vec<&[u8]> = std::vec![b"a", b"bc", b""];
+    assert_eq!(
+        groestl::Groestl256::digest_iter(chunks),
+        groestl::groestl256(b"abc"),
+    );
+}
+
+#[test]
+fn groestl_hash_tree() {
+    use groestl::Groestl256;
+
+    let mut data = [0u8; 1000];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i % 251) as u8;
+    }
+
+    // Three leaves, the last shorter.
+    let mut root = Groestl256::new().chain([1]);
+    for leaf in data[..250].chunks(100) {
+        root.input(&Groestl256::new().chain([0]).chain(leaf).result());
+    }
+    assert_eq!(Groestl256::hash_tree(&data[..250], 100), root.result());
+
+    let leaf = Groestl256::new().chain([0]).result();
+    let empty = Groestl256::new().chain([1]).chain(&leaf[..]).result();
+    assert_eq!(Groestl256::hash_tree(b"", 64), empty);
+
+    // A single leaf is still not the plain digest.
+    assert_ne!(Groestl256::hash_tree(&data, 1000), Groestl256::digest(&data));
+    assert_ne!(
+        Groestl256::hash_tree(&data, 100),
+        Groestl256::hash_tree(&data, 200),
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn groestl_hash_parallel() {
+    let mut data = [0u8; 1000];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i * 7 % 256) as u8;
+    }
+    for &len in &[0, 1, 999, 1000] {
+        for &leaf_size in &[100, 4096] {
+            let data = &data[..len];
+            assert_eq!(
+                groestl::Groestl256::hash_parallel(data, leaf_size),
+                groestl::Groestl256::hash_tree(data, leaf_size),
+            );
+            assert_eq!(
+                groestl::Groestl512::hash_parallel(data, leaf_size),
+                groestl::Groestl512::hash_tree(data, leaf_size),
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "leaf_size must not be zero")]
+fn groestl_hash_tree_zero_leaf() {
+    groestl::Groestl256::hash_tree(b"abc", 0);
+}
+
+#[test]
+fn groestl_fork() {
+    let header = [0x5a; 100];
+    let suffixes: [&[u8]; 4] = [b"", b"a", &[0; 27], &[0xff; 200]];
+
+    let mut base = groestl::Groestl256::new();
+    base.input(&header);
+    for suffix in suffixes.iter() {
+        let mut sh = base.fork();
+        sh.input(suffix);
+        let expected = groestl::Groestl256::new()
+            .chain(header)
+            .chain(suffix)
+            .result();
+        assert_eq!(sh.result(), expected);
+    }
+    assert_eq!(base.result(), groestl::groestl256(&header));
+
+    let mut base = groestl::Groestl384::new();
+    base.input(&header);
+    let mut a = base.fork();
+    let mut b = base.fork();
+    a.input(b"a");
+    b.input(b"b");
+    assert_ne!(a.result(), b.result());
+}
+
+#[test]
+fn groestl_output_transform() {
+    use generic_array::typenum::{U32, U64, U128};
+
+    // Chaining value after the single padded block of the empty message.
+    let mut h = GenericArray::<u8, U64>::default();
+    h[62] = 0x01;
+    let mut m = GenericArray::<u8, U64>::default();
+    m[0] = 0x80;
+    m[63] = 1;
+    groestl::compress(&mut h, &m);
+    assert_eq!(
+        groestl::output_transform::<U32, U64>(&h)[..],
+        include_bytes!("data/groestl256/test1.output.bin")[..],
+    );
+
+    let mut h = GenericArray::<u8, U128>::default();
+    h[126] = 0x02;
+    let mut m = GenericArray::<u8, U128>::default();
+    m[0] = 0x80;
+    m[127] = 1;
+    groestl::compress(&mut h, &m);
+    assert_eq!(
+        groestl::output_transform::<U64, U128>(&h)[..],
+        include_bytes!("data/groestl512/test1.output.bin")[..],
+    );
+}
+
+#[test]
+fn groestl_permutations() {
+    use generic_array::typenum::{U64, U128};
+
+    // The compression function rebuilt from the public permutations.
+    fn check<N>(h: &GenericArray<u8, N>, m: &GenericArray<u8, N>)
+        where N: groestl::ValidBlockSize,
+              N::ArrayType: Copy,
+    {
+        let mut hm = *h;
+        for (a, b) in hm.iter_mut().zip(m.iter()) {
+            *a ^= *b;
+        }
+        let p = groestl::permutation_p(&hm);
+        let q = groestl::permutation_q(m);
+        let mut expected = *h;
+        for i in 0..expected.len() {
+            expected[i] ^= p[i] ^ q[i];
+        }
+        let mut h = *h;
+        groestl::compress(&mut h, m);
+        assert_eq!(h, expected);
+    }
+
+    let mut h = GenericArray::<u8, U64>::default();
+    let mut m = GenericArray::<u8, U64>::default();
+    for i in 0..64 {
+        h[i] = i as u8;
+        m[i] = (i as u8).wrapping_mul(29);
+    }
+    check(&h, &m);
+    assert_ne!(groestl::permutation_p(&m), groestl::permutation_q(&m));
+
+    let mut h = GenericArray::<u8, U128>::default();
+    let mut m = GenericArray::<u8, U128>::default();
+    for i in 0..128 {
+        h[i] = !(i as u8);
+        m[i] = (i as u8).wrapping_mul(53);
+    }
+    check(&h, &m);
+}
+
+#[test]
+fn groestl_process_blocks() {
+    use generic_array::typenum::U64;
+
+    let mut data = [0u8; 3 * 64 + 20];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i * 11 + 1) as u8;
+    }
+    let blocks = [
+        GenericArray::<u8, U64>::clone_from_slice(&data[64..128]),
+        GenericArray::<u8, U64>::clone_from_slice(&data[128..192]),
+    ];
+
+    let mut sh = groestl::Groestl256::new();
+    sh.input(&data[..64]);
+    sh.process_blocks(&blocks);
+    sh.input(&data[192..]);
+    assert_eq!(sh.result(), groestl::groestl256(&data));
+}
+
+#[test]
+#[should_panic]
+fn groestl_process_blocks_unaligned() {
+    use generic_array::typenum::U64;
+
+    let mut sh = groestl::Groestl256::new();
+    sh.input(b"abc");
+    sh.process_blocks(&[GenericArray::<u8, U64>::default()]);
+}
+
+#[test]
+fn groestl_process_with_progress() {
+    let data = [0x3cu8; 64 * 1024 + 100];
+
+    let mut sh = groestl::Groestl256::new();
+    let mut calls = 0;
+    let mut last = 0;
+    sh.process_with_progress(&data, |n| {
+        assert!(n > last);
+        last = n;
+        calls += 1;
+    });
+    assert_eq!(last, data.len());
+    assert_eq!(calls, 2);
+    assert_eq!(sh.result(), groestl::groestl256(&data));
+}
+
+#[test]
+fn groestl_eq() {
+    let data = b"The quick brown fox jumps over the lazy dog, twice over: \
+                 the quick brown fox jumps over the lazy dog";
+
+    let mut a = groestl::Groestl256::new();
+    let mut b = groestl::Groestl256::new();
+    a.input(&data[..70]);
+    for chunk in data[..70].chunks(9) {
+        b.input(chunk);
+    }
+    assert_eq!(a, b);
+
+    b.input(b"x");
+    assert!(a != b);
+    a.input(b"y");
+    assert!(a != b);
+
+    let fresh = groestl::Groestl256::new();
+    assert_eq!(fresh, groestl::Groestl256::new().chain(""));
+}
+
+#[test]
+fn groestl_finalize_slice() {
+    let mut out = [0u8; 32];
+    groestl::Groestl256::new().chain("abc").finalize_slice(&mut out).unwrap();
+    assert_eq!(
+        out[..],
+        include_bytes!("data/groestl256/test4.output.bin")[..],
+    );
+
+    let mut out = [0u8; 64];
+    groestl::Groestl512::new().finalize_slice(&mut out).unwrap();
+    assert_eq!(
+        out[..],
+        include_bytes!("data/groestl512/test1.output.bin")[..],
+    );
+
+    let mut short = [0u8; 31];
+    assert_eq!(
+        groestl::Groestl256::new().finalize_slice(&mut short),
+        Err(groestl::GroestlError::WrongOutputLen {
+            expected: 32,
+            actual: 31,
+        }),
+    );
+    let mut long = [0u8; 33];
+    assert_eq!(
+        groestl::Groestl256::new().finalize_slice(&mut long),
+        Err(groestl::GroestlError::WrongOutputLen {
+            expected: 32,
+            actual: 33,
+        }),
+    );
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn groestl_chaining_values() {
+    // The chaining value of Groestl-256 after each block of "a" * 150, as
+    // printed by the reference implementation; the last is the padding
+    // block.
+    let expected: [[u8; 64]; 3] = [
+        [
+            0x40, 0x52, 0xa7, 0xc1, 0xb2, 0xfd, 0x4b, 0xc9,
+            0x35, 0x81, 0x11, 0x28, 0xd2, 0x91, 0x1a, 0x46,
+            0x68, 0x76, 0x4f, 0x7c, 0xb5, 0xb9, 0x69, 0xd6,
+            0x3e, 0xf6, 0x0d, 0xed, 0xdf, 0x9a, 0x14, 0x9c,
+            0xab, 0x61, 0xb5, 0x84, 0xf5, 0xc3, 0x33, 0x08,
+            0x8e, 0xc6, 0x9c, 0x72, 0x11, 0xfb, 0x70, 0x61,
+            0x52, 0x3f, 0x72, 0xbe, 0x72, 0x22, 0xb7, 0xf9,
+            0x6c, 0x8f, 0x8e, 0x48, 0x3d, 0x71, 0xa5, 0x0b,
+        ],
+        [
+            0x2c, 0xea, 0xce, 0x68, 0x67, 0x35, 0x7a, 0x41,
+            0x22, 0xc2, 0x67, 0x4e, 0x60, 0xd4, 0x92, 0x4d,
+            0x6e, 0x43, 0x7c, 0xf4, 0x8c, 0xaa, 0x90, 0x70,
+            0x66, 0x65, 0xf9, 0x53, 0x6b, 0xf7, 0x29, 0xcf,
+            0x46, 0x88, 0x21, 0x12, 0x4a, 0x59, 0xda, 0xbb,
+            0x60, 0x3e, 0xd2, 0x09, 0x06, 0x92, 0xfc, 0xb0,
+            0x51, 0xa5, 0xbc, 0xe6, 0x0c, 0x18, 0x87, 0x7a,
+            0xfc, 0x7f, 0x9a, 0xef, 0x7f, 0x76, 0xa5, 0x0b,
+        ],
+        [
+            0xc9, 0x3c, 0xc6, 0x8b, 0x36, 0x9d, 0xe5, 0xc8,
+            0x20, 0x48, 0xa2, 0xdb, 0x1b, 0x6f, 0x5a, 0xdb,
+            0xba, 0x42, 0x54, 0xbd, 0xf8, 0xb9, 0xd0, 0x2e,
+            0xdd, 0x40, 0x4b, 0xd2, 0xc8, 0xb8, 0xef, 0xc3,
+            0x5c, 0xa8, 0x91, 0x4d, 0x33, 0x59, 0xc0, 0x5f,
+            0x70, 0xba, 0x60, 0xc9, 0x46, 0xae, 0xbb, 0x5c,
+            0x00, 0xfa, 0x26, 0x1b, 0xc8, 0xe1, 0x48, 0xb1,
+            0x3d, 0x7c, 0x1b, 0xa2, 0x18, 0x78, 0x65, 0x98,
+        ],
+    ];
+    let data = [0x61; 150];
+
+    let mut seen = 0;
+    let mut sh = groestl::Groestl256::new();
+    sh.process_observed(&data, |n, h| {
+        assert_eq!(n, seen + 1);
+        assert_eq!(h[..], expected[seen as usize][..]);
+        seen = n;
+    });
+    assert_eq!(seen, 2);
+    assert_eq!(sh.chaining_value()[..], expected[1][..]);
+
+    let out = sh.finalize_observed(|n, h| {
+        assert_eq!(n, 3);
+        assert_eq!(h[..], expected[2][..]);
+        seen = n;
+    });
+    assert_eq!(seen, 3);
+    assert_eq!(out, groestl::groestl256(&data));
+}
+
+#[cfg(feature = "research")]
+#[test]
+fn groestl_with_rounds() {
+    let data = [0x61; 200];
+
+    let mut sh = groestl::Groestl256::with_rounds(10);
+    sh.input(&data);
+    assert_eq!(sh.result(), groestl::groestl256(&data));
+    let mut sh = groestl::Groestl512::with_rounds(14);
+    sh.input(&data);
+    assert_eq!(sh.result(), groestl::groestl512(&data));
+
+    let mut sh = groestl::Groestl256::with_rounds(4);
+    assert_eq!(sh.rounds(), 4);
+    sh.input(&data);
+    let reduced = sh.finalize_reset();
+    assert_ne!(reduced, groestl::groestl256(&data));
+    assert_eq!(sh.rounds(), 4);
+    sh.input(&data);
+    assert_eq!(sh.result(), reduced);
+
+    let mut sh = groestl::Groestl256::with_rounds(4);
+    sh.rekey(GenericArray::default());
+    assert_eq!(sh.rounds(), 4);
+    sh.reset_with_iv(&GenericArray::default());
+    assert_eq!(sh.rounds(), 4);
+
+    let mut sh = groestl::Groestl512::with_rounds(4);
+    sh.input(&data);
+    assert_ne!(sh.result(), groestl::groestl512(&data));
+}
+
+#[test]
+fn groestl_with_iv_bytes() {
+    let mut iv = [0u8; 128];
+    iv[126] = 0x02;
+    let sh = groestl::Groestl512::with_iv_bytes(&iv).unwrap();
+    assert_eq!(sh, groestl::Groestl512::new());
+
+    let mut iv = [0x17; 64];
+    iv[0] = 0;
+    let sh = groestl::Groestl256::with_iv_bytes(&iv).unwrap();
+    let expected =
+        groestl::Groestl256::with_iv(GenericArray::clone_from_slice(&iv));
+    assert_eq!(sh.chain(b"abc").result(), expected.chain(b"abc").result());
+
+    assert_eq!(
+        groestl::Groestl256::with_iv_bytes(&[0; 63]),
+        Err(groestl::GroestlError::InvalidBlockSize(63)),
+    );
+    assert_eq!(
+        groestl::Groestl256::with_iv_bytes(&[0; 128]),
+        Err(groestl::GroestlError::InvalidBlockSize(128)),
+    );
+    assert_eq!(
+        groestl::Groestl384::with_iv_bytes(&[0; 64]),
+        Err(groestl::GroestlError::InvalidBlockSize(64)),
+    );
+}
+
+#[test]
+fn groestl_rekey() {
+    let mut iv = GenericArray::default();
+    for (i, b) in iv.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let mut sh = groestl::Groestl256::new();
+    sh.input(&[0xee; 100]);
+    sh.rekey(iv);
+    assert_eq!(sh.blocks_processed(), 0);
+    sh.input(b"message");
+    let mut fresh = groestl::Groestl256::with_iv(iv);
+    fresh.input(b"message");
+    assert_eq!(sh, fresh);
+    assert_eq!(sh.result(), fresh.result());
+
+    let mut sh = groestl::Groestl384::new();
+    sh.input(b"first");
+    sh.rekey(GenericArray::default());
+    sh.input(b"second");
+    let fresh = groestl::Groestl384::with_iv(GenericArray::default())
+        .chain(b"second");
+    assert_eq!(sh.result(), fresh.result());
+}
+
+#[test]
+fn groestl_reset_with_iv() {
+    let mut iv = GenericArray::default();
+    for (i, b) in iv.iter_mut().enumerate() {
+        *b = (i as u8).wrapping_mul(7);
+    }
+    let mut sh = groestl::Groestl256::new();
+    sh.input(&[0xee; 100]);
+    sh.reset_with_iv(&iv);
+    assert_eq!(sh, groestl::Groestl256::with_iv(iv));
+    sh.input(b"message");
+    let fresh = groestl::Groestl256::with_iv(iv).chain(b"message");
+    assert_eq!(sh.result(), fresh.result());
+
+    let mut iv = GenericArray::default();
+    iv[0] = 1;
+    let mut sh = groestl::Groestl512::new();
+    sh.process_bits(&[0xff; 3], 19).unwrap();
+    sh.reset_with_iv(&iv);
+    let fresh = groestl::Groestl512::with_iv(iv);
+    assert_eq!(sh, fresh);
+    assert_eq!(sh.result(), fresh.result());
+}
+
+#[test]
+fn groestl_with_prefix() {
+    let msg = [0x5au8; 150];
+
+    let mut sh = groestl::Groestl256::with_prefix(b"\x01node");
+    sh.input(&msg);
+    let mut expected = groestl::Groestl256::new();
+    expected.input(b"\x01node");
+    expected.input(&msg);
+    assert_eq!(sh.result(), expected.result());
+
+    assert_eq!(
+        groestl::Groestl384::with_prefix(b"").result(),
+        groestl::Groestl384::new().result(),
+    );
+}
+
+#[test]
+fn groestl_process_vectored() {
+    let mut msg = [0u8; 300];
+    for (i, b) in msg.iter_mut().enumerate() {
+        *b = (i * 3) as u8;
+    }
+
+    let mut sh = groestl::Groestl512::new();
+    sh.process_vectored(&[&msg[..5], &msg[5..190], &msg[190..]]);
+    assert_eq!(sh.result(), groestl::groestl512(&msg));
+
+    let mut sh = groestl::Groestl256::new();
+    sh.process_vectored(&[]);
+    sh.process_vectored(&[b"", b"abc", b""]);
+    assert_eq!(sh.result(), groestl::groestl256(b"abc"));
+}
+
+#[test]
+fn groestl_var() {
+    use generic_array::typenum::{U20, U40};
+
+    let msg = b"The quick brown fox jumps over the lazy dog";
+    fn var(size: usize, msg: &[u8]) -> [u8; 64] {
+        let mut sh = groestl::GroestlVar::new(size).unwrap();
+        sh.input(msg);
+        let mut out = [0u8; 64];
+        sh.finalize_slice(&mut out[..size]).unwrap();
+        out
+    }
+
+    assert_eq!(
+        var(20, msg)[..20],
+        groestl::GroestlSmall::<U20>::new().chain(msg).result()[..],
+    );
+    assert_eq!(
+        var(28, msg)[..28],
+        groestl::Groestl224::new().chain(msg).result()[..],
+    );
+    assert_eq!(var(32, msg)[..32], groestl::groestl256(msg)[..]);
+    assert_eq!(
+        var(40, msg)[..40],
+        groestl::GroestlBig::<U40>::new().chain(msg).result()[..],
+    );
+    assert_eq!(var(48, msg)[..48], groestl::groestl384(msg)[..]);
+    assert_eq!(var(64, msg)[..], groestl::groestl512(msg)[..]);
+
+    assert_eq!(groestl::GroestlVar::new(33).unwrap().block_size(), 128);
+    for &size in &[0, 65] {
+        assert_eq!(
+            groestl::GroestlVar::new(size).unwrap_err(),
+            groestl::GroestlError::InvalidOutputLen {
+                requested: size,
+                max: 64,
+            },
+        );
+    }
+
+    let mut out = [0u8; 31];
+    assert_eq!(
+        groestl::GroestlVar::new(32).unwrap().finalize_slice(&mut out),
+        Err(groestl::GroestlError::WrongOutputLen {
+            expected: 32,
+            actual: 31,
+        }),
+    );
+}
+
+#[test]
+fn groestl_var_with_iv() {
+    use generic_array::typenum::U64;
+
+    let iv = [0x3c; 128];
+    let mut sh = groestl::GroestlVar::with_iv(20, &iv).unwrap();
+    assert_eq!(sh.block_size(), 128);
+    sh.input(b"abc");
+    let mut out = [0u8; 20];
+    sh.finalize_slice(&mut out).unwrap();
+
+    let mut expected = [0u8; 20];
+    let iv = GenericArray::clone_from_slice(&iv);
+    let mut big = groestl::Groestl512::with_iv(iv);
+    big.input(b"abc");
+    big.finalize_truncated(&mut expected).unwrap();
+    assert_eq!(out, expected);
+
+    // The standard IV gives the standard digest.
+    let mut iv = GenericArray::<u8, U64>::default();
+    iv[62] = 0x01;
+    let mut sh = groestl::GroestlVar::with_iv(32, &iv).unwrap();
+    sh.input(b"abc");
+    let mut out = [0u8; 32];
+    sh.finalize_slice(&mut out).unwrap();
+    assert_eq!(out[..], groestl::groestl256(b"abc")[..]);
+
+    assert_eq!(
+        groestl::GroestlVar::with_iv(32, &[0; 96]).unwrap_err(),
+        groestl::GroestlError::InvalidBlockSize(96),
+    );
+    assert_eq!(
+        groestl::GroestlVar::with_iv(33, &[0; 64]).unwrap_err(),
+        groestl::GroestlError::InvalidOutputLen { requested: 33, max: 32 },
+    );
+}
+
+#[test]
+fn groestl_error_display() {
+    use core::fmt::Write;
+
+    struct Buf {
+        bytes: [u8; 64],
+        len: usize,
+    }
+
+    impl Write for Buf {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let end = self.len + s.len();
+            self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    fn check(err: groestl::GroestlError, expected: &str) {
+        let mut buf = Buf { bytes: [0; 64], len: 0 };
+        write!(buf, "{}", err).unwrap();
+        assert_eq!(&buf.bytes[..buf.len], expected.as_bytes());
+    }
+
+    check(
+        groestl::GroestlError::InvalidOutputLen { requested: 65, max: 64 },
+        "output length 65 is invalid (maximum 64)",
+    );
+    check(
+        groestl::GroestlError::WrongOutputLen { expected: 32, actual: 33 },
+        "output length 33 is wrong (expected 32)",
+    );
+    check(
+        groestl::GroestlError::InvalidBlockSize(96),
+        "invalid block size 96",
+    );
+    check(
+        groestl::GroestlError::InvalidBitLength,
+        "bit length exceeds the input",
+    );
+    check(
+        groestl::GroestlError::OutputTooLong,
+        "requested output is longer than the Groestl block",
+    );
+    check(groestl::GroestlError::InvalidHex, "invalid hex string");
+    check(
+        groestl::GroestlError::SelfTestFailed,
+        "Groestl self-test failed",
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn groestl_var_finalize() {
+    let mut sh = groestl::GroestlVar::new(48).unwrap();
+    sh.input(b"abc");
+    assert_eq!(
+        sh.finalize()[..],
+        include_bytes!("data/groestl384/test4.output.bin")[..],
+    );
+}
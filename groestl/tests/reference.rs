@@ -0,0 +1,153 @@
+//! Checks Groestl-256 against a deliberately naive implementation written
+//! straight from the specification: the S-box is computed from the field
+//! inverse, every step works on an 8x8 byte matrix, and padding is built as a
+//! separate buffer.
+
+extern crate groestl;
+extern crate proptest;
+
+use groestl::Digest;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+type State = [[u8; 8]; 8];
+
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut r = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            r ^= a;
+        }
+        let hi = a & 0x80 != 0;
+        a <<= 1;
+        if hi {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    r
+}
+
+fn sbox(x: u8) -> u8 {
+    // x^254 is the inverse of x, and maps 0 to 0.
+    let mut inv = 1;
+    for bit in (0..8).rev() {
+        inv = mul(inv, inv);
+        if 254 >> bit & 1 != 0 {
+            inv = mul(inv, x);
+        }
+    }
+    let mut s = 0x63;
+    for i in 0..5 {
+        s ^= inv.rotate_left(i);
+    }
+    s
+}
+
+fn permute(mut s: State, q: bool) -> State {
+    let shifts: [usize; 8] = if q {
+        [1, 3, 5, 7, 0, 2, 4, 6]
+    } else {
+        [0, 1, 2, 3, 4, 5, 6, 7]
+    };
+    let b = [2, 2, 3, 4, 5, 3, 5, 7];
+
+    for round in 0..10 {
+        for c in 0..8 {
+            let rc = ((c as u8) << 4) ^ round;
+            if q {
+                for row in s.iter_mut().take(7) {
+                    row[c] ^= 0xff;
+                }
+                s[7][c] ^= rc ^ 0xff;
+            } else {
+                s[0][c] ^= rc;
+            }
+        }
+
+        for row in s.iter_mut() {
+            for x in row.iter_mut() {
+                *x = sbox(*x);
+            }
+        }
+
+        let mut shifted = [[0; 8]; 8];
+        for r in 0..8 {
+            for c in 0..8 {
+                shifted[r][c] = s[r][(c + shifts[r]) % 8];
+            }
+        }
+
+        let mut mixed = [[0; 8]; 8];
+        for i in 0..8 {
+            for j in 0..8 {
+                for k in 0..8 {
+                    mixed[i][j] ^= mul(b[(k + 8 - i) % 8], shifted[k][j]);
+                }
+            }
+        }
+        s = mixed;
+    }
+    s
+}
+
+fn to_state(bytes: &[u8]) -> State {
+    let mut s = [[0; 8]; 8];
+    for (i, &x) in bytes.iter().enumerate() {
+        s[i % 8][i / 8] = x;
+    }
+    s
+}
+
+fn xor(a: &State, b: &State) -> State {
+    let mut r = [[0; 8]; 8];
+    for i in 0..8 {
+        for j in 0..8 {
+            r[i][j] = a[i][j] ^ b[i][j];
+        }
+    }
+    r
+}
+
+fn groestl256(msg: &[u8]) -> Vec<u8> {
+    let mut padded = msg.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    let blocks = (padded.len() / 64 + 1) as u64;
+    for i in (0..8).rev() {
+        padded.push((blocks >> (8 * i)) as u8);
+    }
+
+    let mut iv = [0; 64];
+    iv[62] = 0x01;
+    let mut h = to_state(&iv);
+    for block in padded.chunks(64) {
+        let m = to_state(block);
+        h = xor(&xor(&permute(xor(&h, &m), false), &permute(m, true)), &h);
+    }
+
+    let out = xor(&permute(h, false), &h);
+    (32..64).map(|i| out[i % 8][i / 8]).collect()
+}
+
+#[test]
+fn reference_matches_known_answer() {
+    let expected = [
+        0x1a, 0x52, 0xd1, 0x1d, 0x55, 0x00, 0x39, 0xbe,
+        0x16, 0x10, 0x7f, 0x9c, 0x58, 0xdb, 0x9e, 0xbc,
+        0xc4, 0x17, 0xf1, 0x6f, 0x73, 0x6a, 0xdb, 0x25,
+        0x02, 0x56, 0x71, 0x19, 0xf0, 0x08, 0x34, 0x67,
+    ];
+    assert_eq!(groestl256(b""), expected.to_vec());
+}
+
+proptest! {
+    #[test]
+    fn groestl256_matches_reference(data in vec(any::<u8>(), 0..300)) {
+        let mut sh = groestl::Groestl256::new();
+        sh.input(&data);
+        prop_assert_eq!(sh.result().to_vec(), groestl256(&data));
+    }
+}